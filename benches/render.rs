@@ -47,6 +47,9 @@ const FIXTURES: &[&str] = &[
 const SPAN_FRACTION: f64 = 0.9;
 /// Distance between the declaration and assignment labels.
 const RELATED_SPAN_DELTA: usize = 250;
+/// Diagnostics in one batch, spread evenly over the file like a rule firing
+/// throughout it.
+const BATCH_SIZE: usize = 50;
 
 struct Fixture {
     name: &'static str,
@@ -231,6 +234,25 @@ fn assigned_diagnostic(fixture: &Fixture) -> Error {
     })
 }
 
+/// [`BATCH_SIZE`] one-label warnings spread over the whole file.
+fn batch_diagnostics(fixture: &Fixture) -> Vec<Error> {
+    let source = std::str::from_utf8(fixture.source.data()).expect("fixtures are UTF-8");
+    (1..=BATCH_SIZE)
+        .map(|i| {
+            let offset = fixture.source_len * i / (BATCH_SIZE + 1);
+            Box::new(LintDiagnostic {
+                message: "Variable 'resolve' is declared but never used.",
+                help: "Consider removing this declaration.",
+                labels: vec![LabeledSpan::new_with_span(
+                    Some("'resolve' is declared here".to_string()),
+                    identifier_span_at(source, offset),
+                )],
+                source: Arc::clone(&fixture.source),
+            }) as Error
+        })
+        .collect()
+}
+
 fn bench(c: &mut Criterion) {
     let fixtures: Vec<Fixture> = FIXTURES.iter().copied().map(load_fixture).collect();
 
@@ -282,6 +304,37 @@ fn bench(c: &mut Criterion) {
         }
         group.finish();
     }
+
+    // A file's worth of diagnostics rendered one report at a time, which
+    // scans the file prefix once per report, and as one batch sharing a
+    // single line index.
+    let mut group = c.benchmark_group("render_batch/ci");
+    let handler = ci_handler();
+    for fixture in &fixtures {
+        let diagnostics = batch_diagnostics(fixture);
+        let batch: Vec<&dyn Diagnostic> =
+            diagnostics.iter().map(|diagnostic| diagnostic.as_ref() as &dyn Diagnostic).collect();
+        group.throughput(Throughput::Bytes(fixture.source_len as u64));
+        group.bench_function(BenchmarkId::new("separate", fixture.name), |b| {
+            b.iter(|| {
+                let mut out = String::new();
+                for &diagnostic in &batch {
+                    handler
+                        .render_report(&mut out, black_box(diagnostic))
+                        .expect("render succeeds");
+                }
+                black_box(out);
+            });
+        });
+        group.bench_function(BenchmarkId::new("batched", fixture.name), |b| {
+            b.iter(|| {
+                let mut out = String::new();
+                handler.render_reports(&mut out, black_box(&batch)).expect("render succeeds");
+                black_box(out);
+            });
+        });
+    }
+    group.finish();
 }
 
 criterion_group!(
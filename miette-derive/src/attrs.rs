@@ -0,0 +1,195 @@
+//! Parsing of the `#[diagnostic(..)]`, `#[label]`, `#[source_code]` and
+//! `#[help]` attributes.
+
+use proc_macro2::Span;
+use syn::{
+    Attribute, Ident, LitStr, Path, Token, Type,
+    meta::ParseNestedMeta,
+    parse::{Parse, ParseStream},
+};
+
+use crate::fmt::FmtString;
+
+/// Contents of `#[diagnostic(..)]` on a struct, enum or enum variant.
+#[derive(Default)]
+pub struct DiagnosticAttrs {
+    pub code: Option<String>,
+    pub severity: Option<Ident>,
+    pub help: Option<FmtString>,
+    pub note: Option<FmtString>,
+    pub url: Option<FmtString>,
+}
+
+impl DiagnosticAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("diagnostic")) {
+            attr.parse_nested_meta(|meta| parsed.parse_meta(&meta))?;
+        }
+        Ok(parsed)
+    }
+
+    fn parse_meta(&mut self, meta: &ParseNestedMeta<'_>) -> syn::Result<()> {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        if meta.path.is_ident("code") {
+            let code = if content.peek(LitStr) {
+                content.parse::<LitStr>()?.value()
+            } else {
+                path_to_string(&content.parse::<Path>()?)
+            };
+            set_once(meta, &mut self.code, code)
+        } else if meta.path.is_ident("severity") {
+            let severity = parse_severity(&content)?;
+            set_once(meta, &mut self.severity, severity)
+        } else if meta.path.is_ident("help") {
+            set_once(meta, &mut self.help, content.parse()?)
+        } else if meta.path.is_ident("note") {
+            set_once(meta, &mut self.note, content.parse()?)
+        } else if meta.path.is_ident("url") {
+            set_once(meta, &mut self.url, content.parse()?)
+        } else {
+            Err(meta.error(
+                "unsupported diagnostic attribute, expected one of `code`, `severity`, `help`, \
+                 `note` or `url`",
+            ))
+        }
+    }
+
+    /// Fills every attribute this variant leaves unset from the enum-level
+    /// `defaults`.
+    pub fn or_defaults(self, defaults: &Self) -> Self {
+        Self {
+            code: self.code.or_else(|| defaults.code.clone()),
+            severity: self.severity.or_else(|| defaults.severity.clone()),
+            help: self.help.or_else(|| defaults.help.clone()),
+            note: self.note.or_else(|| defaults.note.clone()),
+            url: self.url.or_else(|| defaults.url.clone()),
+        }
+    }
+}
+
+fn set_once<T>(meta: &ParseNestedMeta<'_>, slot: &mut Option<T>, value: T) -> syn::Result<()> {
+    if slot.replace(value).is_some() {
+        let name = meta.path.get_ident().map(ToString::to_string).unwrap_or_default();
+        return Err(meta.error(format!("duplicate `{name}` attribute")));
+    }
+    Ok(())
+}
+
+fn path_to_string(path: &Path) -> String {
+    let segments = path.segments.iter().map(|segment| segment.ident.to_string());
+    segments.collect::<Vec<_>>().join("::")
+}
+
+/// Accepts `Error`, `error` or `"error"` (and likewise for the other
+/// severities), returning the `miette::Severity` variant.
+fn parse_severity(input: ParseStream<'_>) -> syn::Result<Ident> {
+    let (name, span) = if input.peek(LitStr) {
+        let lit: LitStr = input.parse()?;
+        (lit.value(), lit.span())
+    } else {
+        let ident: Ident = input.parse()?;
+        (ident.to_string(), ident.span())
+    };
+    let variant = match name.as_str() {
+        "Error" | "error" => "Error",
+        "Warning" | "warning" => "Warning",
+        "Advice" | "advice" => "Advice",
+        _ => {
+            return Err(syn::Error::new(
+                span,
+                "unknown severity, expected one of `Error`, `Warning` or `Advice`",
+            ));
+        }
+    };
+    Ok(Ident::new(variant, span))
+}
+
+/// Contents of `#[label]`, `#[label("text")]` or `#[label(primary, "text")]`.
+pub struct LabelAttr {
+    /// The `miette::LabelKind` variant, if one is given.
+    pub kind: Option<Ident>,
+    pub text: Option<FmtString>,
+}
+
+impl Parse for LabelAttr {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let kind = if input.peek(Ident) {
+            let ident: Ident = input.parse()?;
+            let variant = match ident.to_string().as_str() {
+                "primary" => "Primary",
+                "secondary" => "Secondary",
+                "note" => "Note",
+                "added" => "Added",
+                "removed" => "Removed",
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "expected a label string or one of `primary`, `secondary`, `note`, \
+                         `added` or `removed`",
+                    ));
+                }
+            };
+            input.parse::<Option<Token![,]>>()?;
+            Some(Ident::new(variant, ident.span()))
+        } else {
+            None
+        };
+        let text = if input.is_empty() { None } else { Some(input.parse()?) };
+        Ok(Self { kind, text })
+    }
+}
+
+/// The attributes on one field.
+#[derive(Default)]
+pub struct FieldAttrs {
+    pub label: Option<LabelAttr>,
+    pub source_code: Option<Span>,
+    pub help: Option<Span>,
+}
+
+impl FieldAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        for attr in attrs {
+            let span = attr.path().get_ident().map_or_else(Span::call_site, Ident::span);
+            if attr.path().is_ident("label") {
+                let label = match &attr.meta {
+                    syn::Meta::Path(_) => LabelAttr { kind: None, text: None },
+                    _ => attr.parse_args()?,
+                };
+                if parsed.label.replace(label).is_some() {
+                    return Err(syn::Error::new(span, "duplicate `label` attribute"));
+                }
+            } else if attr.path().is_ident("source_code") {
+                attr.meta.require_path_only()?;
+                parsed.source_code = Some(span);
+            } else if attr.path().is_ident("help") {
+                attr.meta.require_path_only()?;
+                parsed.help = Some(span);
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// The `T` of an `Option<T>` field type.
+pub fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+/// Whether a field holds the `miette::LabelStorage` for derived labels.
+pub fn is_label_storage(ty: &Type) -> bool {
+    let Type::Path(path) = ty else { return false };
+    path.path.segments.last().is_some_and(|segment| segment.ident == "LabelStorage")
+}
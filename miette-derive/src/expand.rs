@@ -0,0 +1,323 @@
+//! Generates the `Diagnostic` impl.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, Ident, Member, Type};
+
+use crate::{
+    attrs::{DiagnosticAttrs, FieldAttrs, LabelAttr, is_label_storage, option_inner},
+    fmt::FmtString,
+};
+
+struct Field<'a> {
+    binding: Ident,
+    member: Member,
+    ty: &'a Type,
+    attrs: FieldAttrs,
+}
+
+/// A struct, or one variant of an enum.
+struct Variant<'a> {
+    path: TokenStream,
+    span: Span,
+    attrs: DiagnosticAttrs,
+    fields: Vec<Field<'a>>,
+}
+
+pub fn derive(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let attrs = DiagnosticAttrs::from_attrs(&input.attrs)?;
+    let variants = match &input.data {
+        Data::Struct(data) => {
+            vec![Variant::new(quote!(Self), input.ident.span(), attrs, &data.fields)?]
+        }
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let ident = &variant.ident;
+                let variant_attrs = DiagnosticAttrs::from_attrs(&variant.attrs)?;
+                let attrs = variant_attrs.or_defaults(&attrs);
+                Variant::new(quote!(Self::#ident), ident.span(), attrs, &variant.fields)
+            })
+            .collect::<syn::Result<_>>()?,
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "`Diagnostic` cannot be derived for unions",
+            ));
+        }
+    };
+
+    let cow = quote!(::std::option::Option<::std::borrow::Cow<'_, str>>);
+    let none = quote!(::std::option::Option::None);
+    let methods: [(TokenStream, &TokenStream, Body<'_>); 7] = [
+        (quote!(fn code(&self) -> #cow), &none, Variant::code),
+        (
+            quote!(fn severity(&self) -> ::std::option::Option<::miette::Severity>),
+            &none,
+            Variant::severity,
+        ),
+        (quote!(fn help(&self) -> #cow), &none, Variant::help),
+        (quote!(fn note(&self) -> #cow), &none, Variant::note),
+        (quote!(fn url(&self) -> #cow), &none, Variant::url),
+        (
+            quote!(fn source_code(&self) -> ::std::option::Option<&dyn ::miette::SourceCode>),
+            &none,
+            Variant::source_code,
+        ),
+        (quote!(fn labels(&self) -> &[::miette::LabeledSpan]), &quote!(&[]), Variant::labels),
+    ];
+    let methods = methods
+        .into_iter()
+        .map(|(signature, default, body)| method(&variants, &signature, default, body))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::miette::Diagnostic for #ident #ty_generics #where_clause {
+            #(#methods)*
+        }
+    })
+}
+
+/// The fields a match arm binds, and the expression it evaluates to.
+type Arm = (Vec<Ident>, TokenStream);
+
+/// Builds a variant's arm for one method, or `None` to use the default.
+type Body<'a> = fn(&Variant<'a>) -> syn::Result<Option<Arm>>;
+
+/// Builds one trait method as a `match self` over every variant, or nothing
+/// when no variant overrides the default.
+fn method<'a>(
+    variants: &[Variant<'a>],
+    signature: &TokenStream,
+    default: &TokenStream,
+    body: Body<'a>,
+) -> syn::Result<TokenStream> {
+    let bodies = variants.iter().map(body).collect::<syn::Result<Vec<_>>>()?;
+    if bodies.iter().all(Option::is_none) {
+        return Ok(TokenStream::new());
+    }
+    let arms = variants.iter().zip(bodies).map(|(variant, body)| {
+        if let Some((used, expr)) = body {
+            let pattern = variant.pattern(&used);
+            quote!(#pattern => #expr,)
+        } else {
+            let path = &variant.path;
+            quote!(#path { .. } => #default,)
+        }
+    });
+    Ok(quote! {
+        #signature {
+            match self {
+                #(#arms)*
+            }
+        }
+    })
+}
+
+impl<'a> Variant<'a> {
+    fn new(
+        path: TokenStream,
+        span: Span,
+        attrs: DiagnosticAttrs,
+        fields: &'a Fields,
+    ) -> syn::Result<Self> {
+        let fields = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let (binding, member) = field.ident.as_ref().map_or_else(
+                    || (format_ident!("_{}", index), Member::from(index)),
+                    |ident| (ident.clone(), Member::from(ident.clone())),
+                );
+                Ok(Field {
+                    binding,
+                    member,
+                    ty: &field.ty,
+                    attrs: FieldAttrs::from_attrs(&field.attrs)?,
+                })
+            })
+            .collect::<syn::Result<_>>()?;
+        Ok(Self { path, span, attrs, fields })
+    }
+
+    /// `Path { field, 0: _0, .. }`, binding only the fields in `used`.
+    fn pattern(&self, used: &[Ident]) -> TokenStream {
+        let path = &self.path;
+        let bindings = self.fields.iter().filter(|field| used.contains(&field.binding)).map(
+            |Field { binding, member, .. }| match member {
+                Member::Named(_) => quote!(#binding),
+                Member::Unnamed(_) => quote!(#member: #binding),
+            },
+        );
+        quote!(#path { #(#bindings,)* .. })
+    }
+
+    fn fmt_captures(&self, fmt: &FmtString) -> Vec<Ident> {
+        let fields = self.fields.iter().map(|field| &field.binding);
+        fields.filter(|binding| fmt.captures().contains(binding)).cloned().collect()
+    }
+
+    fn fmt_cow(&self, fmt: &FmtString) -> Arm {
+        let cow = fmt.to_cow();
+        (self.fmt_captures(fmt), quote!(::std::option::Option::Some(#cow)))
+    }
+
+    /// The single field carrying `attr`, if any.
+    fn field_with<T>(
+        &self,
+        attr: impl Fn(&FieldAttrs) -> Option<&T>,
+        name: &str,
+    ) -> syn::Result<Option<&Field<'a>>> {
+        let mut fields = self.fields.iter().filter(|field| attr(&field.attrs).is_some());
+        let first = fields.next();
+        if fields.next().is_some() {
+            return Err(syn::Error::new(self.span, format!("only one field may be `#[{name}]`")));
+        }
+        Ok(first)
+    }
+
+    #[expect(clippy::unnecessary_wraps, reason = "all method bodies share a signature")]
+    fn code(&self) -> syn::Result<Option<Arm>> {
+        Ok(self.attrs.code.as_ref().map(|code| {
+            (Vec::new(), quote!(::std::option::Option::Some(::std::borrow::Cow::Borrowed(#code))))
+        }))
+    }
+
+    #[expect(clippy::unnecessary_wraps, reason = "all method bodies share a signature")]
+    fn severity(&self) -> syn::Result<Option<Arm>> {
+        Ok(self.attrs.severity.as_ref().map(|severity| {
+            (Vec::new(), quote!(::std::option::Option::Some(::miette::Severity::#severity)))
+        }))
+    }
+
+    fn help(&self) -> syn::Result<Option<Arm>> {
+        let Some(field) = self.field_with(|attrs| attrs.help.as_ref(), "help")? else {
+            return Ok(self.attrs.help.as_ref().map(|help| self.fmt_cow(help)));
+        };
+        if self.attrs.help.is_some() {
+            return Err(syn::Error::new(
+                self.span,
+                "help is given both by `#[diagnostic(help(..))]` and a `#[help]` field",
+            ));
+        }
+        let binding = &field.binding;
+        let expr = if let Some(inner) = option_inner(field.ty) {
+            quote! {
+                #binding.as_ref().map(|help| {
+                    ::std::borrow::Cow::Borrowed(<#inner as ::std::convert::AsRef<str>>::as_ref(help))
+                })
+            }
+        } else {
+            let ty = field.ty;
+            quote! {
+                ::std::option::Option::Some(::std::borrow::Cow::Borrowed(
+                    <#ty as ::std::convert::AsRef<str>>::as_ref(#binding),
+                ))
+            }
+        };
+        Ok(Some((vec![binding.clone()], expr)))
+    }
+
+    #[expect(clippy::unnecessary_wraps, reason = "all method bodies share a signature")]
+    fn note(&self) -> syn::Result<Option<Arm>> {
+        Ok(self.attrs.note.as_ref().map(|note| self.fmt_cow(note)))
+    }
+
+    #[expect(clippy::unnecessary_wraps, reason = "all method bodies share a signature")]
+    fn url(&self) -> syn::Result<Option<Arm>> {
+        Ok(self.attrs.url.as_ref().map(|url| self.fmt_cow(url)))
+    }
+
+    fn source_code(&self) -> syn::Result<Option<Arm>> {
+        let Some(field) = self.field_with(|attrs| attrs.source_code.as_ref(), "source_code")?
+        else {
+            return Ok(None);
+        };
+        let binding = &field.binding;
+        let expr = if option_inner(field.ty).is_some() {
+            quote!(#binding.as_ref().map(|source| source as &dyn ::miette::SourceCode))
+        } else {
+            quote!(::std::option::Option::Some(#binding as &dyn ::miette::SourceCode))
+        };
+        Ok(Some((vec![binding.clone()], expr)))
+    }
+
+    fn labels(&self) -> syn::Result<Option<Arm>> {
+        let labels: Vec<_> = self
+            .fields
+            .iter()
+            .filter_map(|field| field.attrs.label.as_ref().map(|label| (field, label)))
+            .collect();
+        let mut storages = self.fields.iter().filter(|field| is_label_storage(field.ty));
+        let storage = storages.next();
+        if storages.next().is_some() {
+            return Err(syn::Error::new(self.span, "only one field may be a `LabelStorage`"));
+        }
+        if labels.is_empty() {
+            return Ok(None);
+        }
+        let Some(storage) = storage else {
+            return Err(syn::Error::new(
+                self.span,
+                "`#[label]` fields need a `miette::LabelStorage` field to hold the built labels",
+            ));
+        };
+
+        let mut used = vec![storage.binding.clone()];
+        let pushes = labels.iter().map(|(field, label)| {
+            used.push(field.binding.clone());
+            if let Some(text) = &label.text {
+                used.extend(self.fmt_captures(text));
+            }
+            Self::push_label(field, label)
+        });
+        let pushes: Vec<_> = pushes.collect();
+        let storage = &storage.binding;
+        let capacity = labels.len();
+        let expr = quote! {
+            #storage.get_or_init(|| {
+                let mut __labels = ::std::vec::Vec::with_capacity(#capacity);
+                #(#pushes)*
+                __labels
+            })
+        };
+        Ok(Some((used, expr)))
+    }
+
+    fn push_label(field: &Field<'_>, label: &LabelAttr) -> TokenStream {
+        let binding = &field.binding;
+        let text = label.text.as_ref().map_or_else(
+            || quote!(::std::option::Option::None),
+            |text| {
+                let text = text.to_string_expr();
+                quote!(::std::option::Option::Some(#text))
+            },
+        );
+        let kind = label.kind.as_ref().map_or_else(
+            || quote!(::miette::LabelKind::Secondary),
+            |kind| quote!(::miette::LabelKind::#kind),
+        );
+        let push = |span: TokenStream| {
+            quote! {
+                __labels.push(::miette::LabeledSpan::new_with_kind(
+                    #text,
+                    ::miette::SourceSpan::from(::std::clone::Clone::clone(#span)),
+                    #kind,
+                ));
+            }
+        };
+        if option_inner(field.ty).is_some() {
+            let push = push(quote!(span));
+            quote! {
+                if let ::std::option::Option::Some(span) = #binding {
+                    #push
+                }
+            }
+        } else {
+            push(quote!(#binding))
+        }
+    }
+}
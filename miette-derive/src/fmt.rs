@@ -0,0 +1,138 @@
+//! Format strings in attributes, such as `help("expected {expected}")`.
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use syn::{
+    Ident, LitStr, Token,
+    parse::{Parse, ParseStream},
+};
+
+/// A format string literal plus any explicit arguments after it.
+///
+/// Named placeholders that match a field are captured from the field's
+/// binding. Positional placeholders like `{0}` refer to tuple fields when no
+/// explicit arguments are given, and are rewritten to the `_0` bindings.
+#[derive(Clone)]
+pub struct FmtString {
+    lit: LitStr,
+    args: TokenStream,
+    captures: Vec<Ident>,
+}
+
+impl Parse for FmtString {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let lit: LitStr = input.parse()?;
+        let args = if input.parse::<Option<Token![,]>>()?.is_some() {
+            input.parse()?
+        } else {
+            TokenStream::new()
+        };
+        let (value, mut captures) = rewrite(&lit.value(), args.is_empty());
+        collect_idents(args.clone(), &mut captures);
+        Ok(Self { lit: LitStr::new(&value, lit.span()), args, captures })
+    }
+}
+
+impl FmtString {
+    /// Identifiers the format string and its arguments may refer to. Only the
+    /// ones that name a field need to be bound.
+    pub fn captures(&self) -> &[Ident] {
+        &self.captures
+    }
+
+    fn is_plain(&self) -> bool {
+        self.args.is_empty() && !self.lit.value().contains(['{', '}'])
+    }
+
+    /// A `Cow<'_, str>` expression.
+    pub fn to_cow(&self) -> TokenStream {
+        let Self { lit, args, .. } = self;
+        if self.is_plain() {
+            quote!(::std::borrow::Cow::Borrowed(#lit))
+        } else {
+            quote!(::std::borrow::Cow::Owned(::std::format!(#lit, #args)))
+        }
+    }
+
+    /// A `String` expression.
+    pub fn to_string_expr(&self) -> TokenStream {
+        let Self { lit, args, .. } = self;
+        if self.is_plain() {
+            quote!(::std::string::String::from(#lit))
+        } else {
+            quote!(::std::format!(#lit, #args))
+        }
+    }
+}
+
+fn collect_idents(tokens: TokenStream, idents: &mut Vec<Ident>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => idents.push(ident),
+            TokenTree::Group(group) => collect_idents(group.stream(), idents),
+            TokenTree::Punct(_) | TokenTree::Literal(_) => {}
+        }
+    }
+}
+
+/// Collects the implicit captures of `value`, rewriting tuple-field
+/// placeholders (`{0}`) to their bindings (`{_0}`) when `rewrite_positional`.
+fn rewrite(value: &str, rewrite_positional: bool) -> (String, Vec<Ident>) {
+    let mut output = String::with_capacity(value.len());
+    let mut captures = Vec::new();
+    let mut rest = value;
+    while let Some(open) = rest.find('{') {
+        output.push_str(&rest[..=open]);
+        rest = &rest[open + 1..];
+        if let Some(after) = rest.strip_prefix('{') {
+            output.push('{');
+            rest = after;
+            continue;
+        }
+        let name_len = rest.find(['}', ':']).unwrap_or(rest.len());
+        let name = rest[..name_len].trim();
+        if !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit()) {
+            if rewrite_positional {
+                let binding = format_ident!("_{}", name);
+                output.push_str(&binding.to_string());
+                captures.push(binding);
+                rest = &rest[name_len..];
+            }
+        } else if syn::parse_str::<Ident>(name).is_ok() {
+            captures.push(format_ident!("{}", name));
+        }
+    }
+    output.push_str(rest);
+    (output, captures)
+}
+
+#[cfg(test)]
+#[expect(clippy::literal_string_with_formatting_args, reason = "format strings are the input")]
+mod tests {
+    use super::*;
+
+    fn names(captures: &[Ident]) -> Vec<String> {
+        captures.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn collects_named_captures() {
+        let (value, captures) = rewrite("expected {expected}, found {found:?}", true);
+        assert_eq!(value, "expected {expected}, found {found:?}");
+        assert_eq!(names(&captures), ["expected", "found"]);
+    }
+
+    #[test]
+    fn rewrites_tuple_fields() {
+        let (value, captures) = rewrite("{0} and {1:>4}", true);
+        assert_eq!(value, "{_0} and {_1:>4}");
+        assert_eq!(names(&captures), ["_0", "_1"]);
+    }
+
+    #[test]
+    fn keeps_positional_arguments_and_escapes() {
+        let (value, captures) = rewrite("{{literal}} {0} {}", false);
+        assert_eq!(value, "{{literal}} {0} {}");
+        assert!(captures.is_empty());
+    }
+}
@@ -0,0 +1,73 @@
+//! `#[derive(Diagnostic)]` for [oxc-miette](https://docs.rs/oxc-miette).
+//!
+//! Use it through the `derive` feature of `oxc-miette`, which re-exports the
+//! macro as `miette::Diagnostic`. The type must implement `Display` and
+//! `Error` itself.
+//!
+//! # Attributes
+//!
+//! On the struct, enum or enum variant, `#[diagnostic(..)]` takes:
+//! - `code(some::code)` or `code("some::code")`
+//! - `severity(Warning)`, one of `Error`, `Warning` or `Advice`
+//! - `help("..")`, `note("..")` and `url("..")`, format strings that may
+//!   refer to fields by name (or by `{0}` in tuple structs) or take explicit
+//!   arguments after the string
+//!
+//! Attributes on an enum are defaults that each variant can override.
+//!
+//! On fields:
+//! - `#[label]`, `#[label("text")]` or `#[label(primary, "text")]` marks a
+//!   span, anything `Into<SourceSpan> + Clone` or an `Option` of it. The
+//!   optional kind is one of `primary`, `secondary`, `note`, `added` or
+//!   `removed`, after `miette::LabelKind`. A variant with labels also needs a
+//!   `miette::LabelStorage` field to hold them.
+//! - `#[source_code]` marks the `SourceCode` the labels point into, or an
+//!   `Option` of it.
+//! - `#[help]` takes the help text from a string field, or an `Option` of one.
+//!
+//! ```
+//! use miette::{Diagnostic, LabelStorage, SourceSpan};
+//!
+//! #[derive(Debug, Diagnostic)]
+//! #[diagnostic(code(parser::unexpected), help("remove `{token}`"))]
+//! struct Unexpected {
+//!     token: String,
+//!     #[source_code]
+//!     source: String,
+//!     #[label("unexpected `{token}`")]
+//!     span: SourceSpan,
+//!     labels: LabelStorage,
+//! }
+//!
+//! impl std::fmt::Display for Unexpected {
+//!     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+//!         write!(f, "unexpected token")
+//!     }
+//! }
+//!
+//! impl std::error::Error for Unexpected {}
+//!
+//! let diagnostic = Unexpected {
+//!     token: "?".into(),
+//!     source: "let ? = 1;".into(),
+//!     span: (4, 1).into(),
+//!     labels: LabelStorage::new(),
+//! };
+//! assert_eq!(diagnostic.help().as_deref(), Some("remove `?`"));
+//! assert_eq!(diagnostic.labels()[0].label(), Some("unexpected `?`"));
+//! ```
+
+mod attrs;
+mod expand;
+mod fmt;
+
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
+
+/// Derives `miette::Diagnostic`. See the [crate docs](crate) for the
+/// attributes it accepts.
+#[proc_macro_derive(Diagnostic, attributes(diagnostic, label, source_code, help))]
+pub fn derive_diagnostic(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand::derive(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
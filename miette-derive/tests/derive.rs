@@ -0,0 +1,153 @@
+use std::{fmt, ops::Range, sync::Arc};
+
+use miette::{
+    Diagnostic, GraphicalReportHandler, GraphicalTheme, LabelKind, LabelStorage, LabeledSpan,
+    NamedSource, Severity, SourceCode, SourceSpan,
+};
+
+macro_rules! display {
+    ($ty:ty, $message:literal) => {
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str($message)
+            }
+        }
+
+        impl std::error::Error for $ty {}
+    };
+}
+
+#[derive(Debug, Diagnostic)]
+#[diagnostic(
+    code(parser::unexpected),
+    severity(Warning),
+    help("remove `{token}`"),
+    note("`{}` is not an identifier", token),
+    url("https://oxc.rs/docs/{code}", code = "unexpected")
+)]
+struct Unexpected {
+    token: String,
+    #[source_code]
+    source: NamedSource<String>,
+    #[label(primary, "unexpected `{token}`")]
+    span: SourceSpan,
+    #[label(note, "statement starts here")]
+    start: Option<Range<u32>>,
+    labels: LabelStorage,
+}
+display!(Unexpected, "unexpected token");
+
+fn unexpected() -> Unexpected {
+    Unexpected {
+        token: "?".into(),
+        source: NamedSource::new("test.js", "let ? = 1;".into()),
+        span: (4, 1).into(),
+        start: Some(0..3),
+        labels: LabelStorage::new(),
+    }
+}
+
+#[test]
+fn struct_attributes() {
+    let diagnostic = unexpected();
+    assert_eq!(diagnostic.code().as_deref(), Some("parser::unexpected"));
+    assert_eq!(diagnostic.severity(), Some(Severity::Warning));
+    assert_eq!(diagnostic.help().as_deref(), Some("remove `?`"));
+    assert_eq!(diagnostic.note().as_deref(), Some("`?` is not an identifier"));
+    assert_eq!(diagnostic.url().as_deref(), Some("https://oxc.rs/docs/unexpected"));
+    assert_eq!(diagnostic.source_code().and_then(SourceCode::name), Some("test.js"));
+}
+
+#[test]
+fn labels_are_built_from_fields() {
+    let diagnostic = unexpected();
+    assert_eq!(
+        diagnostic.labels(),
+        [
+            LabeledSpan::new_primary_with_span(Some("unexpected `?`".into()), 4..5),
+            LabeledSpan::new_with_kind(Some("statement starts here".into()), 0..3, LabelKind::Note),
+        ]
+    );
+
+    let diagnostic = Unexpected { start: None, ..unexpected() };
+    assert_eq!(diagnostic.labels().len(), 1);
+}
+
+#[test]
+fn renders_derived_diagnostic() {
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .with_links(false)
+        .render_report(&mut out, &unexpected())
+        .unwrap();
+    let expected = r"
+  ! parser::unexpected: unexpected token
+   ,-[test.js:1:5]
+ 1 | let ? = 1;
+   : .|. ^
+   :  |  `-- unexpected `?`
+   :  `-- statement starts here
+   `----
+  help: remove `?`
+  note: `?` is not an identifier
+";
+    assert_eq!(out, expected);
+}
+
+#[derive(Debug, Diagnostic)]
+#[diagnostic(code("lint::default"), severity(error))]
+enum Lint {
+    #[diagnostic(code(lint::no_debugger), help("remove the debugger statement"))]
+    NoDebugger {
+        #[label]
+        span: SourceSpan,
+        labels: LabelStorage,
+    },
+    #[diagnostic(severity("advice"))]
+    Tuple(#[help] Option<String>, #[label("{1:?}")] (u32, u32), LabelStorage),
+    Unit,
+}
+display!(Lint, "lint");
+
+#[test]
+fn enum_variants_override_defaults() {
+    let no_debugger = Lint::NoDebugger { span: (2, 8).into(), labels: LabelStorage::new() };
+    assert_eq!(no_debugger.code().as_deref(), Some("lint::no_debugger"));
+    assert_eq!(no_debugger.severity(), Some(Severity::Error));
+    assert_eq!(no_debugger.help().as_deref(), Some("remove the debugger statement"));
+    assert_eq!(no_debugger.labels(), [LabeledSpan::new(None, 2, 8)]);
+
+    let tuple = Lint::Tuple(Some("help field".into()), (1, 2), LabelStorage::new());
+    assert_eq!(tuple.code().as_deref(), Some("lint::default"));
+    assert_eq!(tuple.severity(), Some(Severity::Advice));
+    assert_eq!(tuple.help().as_deref(), Some("help field"));
+    assert_eq!(tuple.labels(), [LabeledSpan::new(Some("(1, 2)".into()), 1, 2)]);
+
+    assert_eq!(Lint::Unit.code().as_deref(), Some("lint::default"));
+    assert_eq!(Lint::Unit.help(), None);
+    assert!(Lint::Unit.labels().is_empty());
+    assert!(Lint::Unit.source_code().is_none());
+}
+
+#[derive(Debug, Diagnostic)]
+struct Generic<S: SourceCode + fmt::Debug> {
+    #[source_code]
+    source: Option<Arc<S>>,
+}
+
+impl<S: SourceCode + fmt::Debug> fmt::Display for Generic<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("generic")
+    }
+}
+
+impl<S: SourceCode + fmt::Debug> std::error::Error for Generic<S> {}
+
+#[test]
+fn generic_optional_source_code() {
+    let diagnostic = Generic { source: Some(Arc::new(String::from("source"))) };
+    assert_eq!(diagnostic.source_code().map(SourceCode::data), Some(&b"source"[..]));
+    assert!(Generic::<String> { source: None }.source_code().is_none());
+    assert_eq!(diagnostic.code(), None);
+    assert!(diagnostic.labels().is_empty());
+}
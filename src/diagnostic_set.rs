@@ -0,0 +1,194 @@
+//! The diagnostics of a whole run, collected before they are reported.
+
+use rustc_hash::FxHashSet;
+
+use crate::{Diagnostic, DiagnosticSummary, LabelKind, Severity, SourceCode, SourceSpan};
+
+/// Diagnostics collected from a run, such as every lint of every file, with
+/// the plumbing needed before reporting them: sorting, deduplication,
+/// severity counts and a cap on how many are shown.
+///
+/// Diagnostics without a [`Diagnostic::severity`] count as errors, as the
+/// renderers show them.
+///
+/// # Examples
+/// ```
+/// use miette::{DiagnosticSet, MietteDiagnostic, Severity};
+///
+/// let mut set = DiagnosticSet::new().with_limit(1);
+/// set.push(Box::new(MietteDiagnostic::new("unused variable").with_severity(Severity::Warning)));
+/// set.push(Box::new(MietteDiagnostic::new("unexpected token")));
+/// set.push(Box::new(MietteDiagnostic::new("unexpected token")));
+/// set.dedup();
+/// assert_eq!(set.count(Severity::Error), 1);
+/// assert!(set.has_errors());
+/// assert_eq!(set.shown().len(), 1);
+/// assert_eq!(set.omitted(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct DiagnosticSet {
+    diagnostics: Vec<Box<dyn Diagnostic + Send + Sync>>,
+    limit: Option<usize>,
+}
+
+impl DiagnosticSet {
+    /// Creates an empty set without a limit.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { diagnostics: Vec::new(), limit: None }
+    }
+
+    /// Shows at most `limit` diagnostics. The rest are still counted, and
+    /// [`DiagnosticSet::omitted`] says how many were left out.
+    #[must_use]
+    pub const fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Adds a diagnostic at the end.
+    pub fn push(&mut self, diagnostic: Box<dyn Diagnostic + Send + Sync>) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// The number of diagnostics, including omitted ones.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    /// Whether the set has no diagnostics.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Sorts by the file, line and column of each diagnostic's primary label,
+    /// or its first label if none is primary. Diagnostics without a location
+    /// come first, and the sort is stable.
+    pub fn sort(&mut self) {
+        self.diagnostics.sort_by_cached_key(|diagnostic| location(&**diagnostic));
+    }
+
+    /// Removes diagnostics identical to an earlier one: the same message,
+    /// code, severity, help, source name and labels.
+    pub fn dedup(&mut self) {
+        let mut seen = FxHashSet::default();
+        self.diagnostics.retain(|diagnostic| seen.insert(identity(&**diagnostic)));
+    }
+
+    /// Keeps only the diagnostics for which `keep` returns `true`.
+    pub fn retain(&mut self, mut keep: impl FnMut(&dyn Diagnostic) -> bool) {
+        self.diagnostics.retain(|diagnostic| keep(&**diagnostic));
+    }
+
+    /// Removes diagnostics less severe than `min`, such as advice when only
+    /// warnings and errors are wanted.
+    pub fn retain_severity(&mut self, min: Severity) {
+        self.retain(|diagnostic| severity(diagnostic) >= min);
+    }
+
+    /// The number of diagnostics of `severity`, including omitted ones.
+    #[must_use]
+    pub fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| self::severity(&***diagnostic) == severity)
+            .count()
+    }
+
+    /// Whether any diagnostic is an error, which usually means the run
+    /// failed.
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|diagnostic| severity(&**diagnostic) == Severity::Error)
+    }
+
+    /// The diagnostics to report, at most the limit.
+    #[must_use]
+    pub fn shown(&self) -> &[Box<dyn Diagnostic + Send + Sync>] {
+        let len =
+            self.limit.map_or(self.diagnostics.len(), |limit| limit.min(self.diagnostics.len()));
+        &self.diagnostics[..len]
+    }
+
+    /// The number of diagnostics left out of [`DiagnosticSet::shown`].
+    #[must_use]
+    pub fn omitted(&self) -> usize {
+        self.diagnostics.len() - self.shown().len()
+    }
+
+    /// Counts every diagnostic, including omitted ones, for the summary
+    /// printed after the shown ones.
+    #[must_use]
+    pub fn summary(&self) -> DiagnosticSummary {
+        DiagnosticSummary::new(
+            self.diagnostics.iter().map(|diagnostic| &**diagnostic as &dyn Diagnostic),
+        )
+    }
+
+    /// Every diagnostic, including omitted ones.
+    #[must_use]
+    pub fn into_vec(self) -> Vec<Box<dyn Diagnostic + Send + Sync>> {
+        self.diagnostics
+    }
+}
+
+impl Extend<Box<dyn Diagnostic + Send + Sync>> for DiagnosticSet {
+    fn extend<I: IntoIterator<Item = Box<dyn Diagnostic + Send + Sync>>>(&mut self, iter: I) {
+        self.diagnostics.extend(iter);
+    }
+}
+
+impl FromIterator<Box<dyn Diagnostic + Send + Sync>> for DiagnosticSet {
+    fn from_iter<I: IntoIterator<Item = Box<dyn Diagnostic + Send + Sync>>>(iter: I) -> Self {
+        Self { diagnostics: iter.into_iter().collect(), limit: None }
+    }
+}
+
+fn severity(diagnostic: &dyn Diagnostic) -> Severity {
+    diagnostic.severity().unwrap_or_default()
+}
+
+/// The file name and byte offset in that file of `diagnostic`. Offsets sort
+/// the same as lines and columns, without scanning the source.
+fn location(diagnostic: &dyn Diagnostic) -> Option<(Option<String>, u64)> {
+    let labels = diagnostic.labels();
+    let label = labels.iter().find(|label| label.primary()).or_else(|| labels.first())?;
+    let source = diagnostic.source_code();
+    let remapped = match label.source_code() {
+        None => source.and_then(|source| source.remap_label(label)),
+        Some(_) => None,
+    };
+    let label = remapped.as_ref().unwrap_or(label);
+    let source = label.source_code().or(source);
+    let origin = source.map_or(0, |source| source.origin().offset);
+    let name = source.and_then(SourceCode::name).map(String::from);
+    Some((name, u64::from(origin) + u64::from(label.offset())))
+}
+
+/// What two diagnostics must share to be duplicates.
+type Identity = (
+    String,
+    Option<String>,
+    Severity,
+    Option<String>,
+    Option<String>,
+    Vec<(Option<String>, SourceSpan, LabelKind, Option<String>)>,
+);
+
+fn identity(diagnostic: &dyn Diagnostic) -> Identity {
+    let name =
+        |source: Option<&dyn SourceCode>| source.and_then(SourceCode::name).map(String::from);
+    let labels = diagnostic.labels().iter().map(|label| {
+        (label.label().map(String::from), *label.inner(), label.kind(), name(label.source_code()))
+    });
+    (
+        diagnostic.to_string(),
+        diagnostic.code().map(Into::into),
+        severity(diagnostic),
+        diagnostic.help().map(Into::into),
+        name(diagnostic.source_code()),
+        labels.collect(),
+    )
+}
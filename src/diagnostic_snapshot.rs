@@ -0,0 +1,135 @@
+//! An owned copy of any [`Diagnostic`], for sending it elsewhere.
+
+use std::{borrow::Cow, error::Error, fmt};
+
+use crate::{Diagnostic, LabeledSpan, Severity};
+
+/// Owned snapshot of a [`Diagnostic`]'s message, metadata and labels.
+///
+/// Unlike the diagnostic it was taken from, a snapshot is plain data: it can
+/// be sent to another thread or, with the `serde` feature, serialized and
+/// sent to another process. No source code is copied. The diagnostic's
+/// source is kept by name only, and labels pointing into a source of their
+/// own lose it, so their spans are read against the diagnostic's source.
+/// The receiver reattaches the source to render snippets.
+///
+/// With the `serde` feature, a snapshot can be read from the output of
+/// [`JSONReportHandler`](crate::JSONReportHandler), which ignores the
+/// members the renderer adds, such as the causes and the lines and columns
+/// of spans. A serialized snapshot has none of those members, so it is not
+/// itself renderer output.
+///
+/// # Examples
+/// ```
+/// use miette::{Diagnostic, DiagnosticSnapshot, LabeledSpan, MietteDiagnostic, Severity};
+///
+/// let diagnostic = MietteDiagnostic::new("unexpected token")
+///     .with_code("parser::unexpected")
+///     .with_label(LabeledSpan::at(4..5, "here"));
+/// let snapshot = DiagnosticSnapshot::new(&diagnostic);
+/// assert_eq!(snapshot.message, "unexpected token");
+/// assert_eq!(snapshot.severity, Severity::Error);
+/// assert_eq!(snapshot.labels(), diagnostic.labels());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiagnosticSnapshot {
+    /// The diagnostic's `Display` output.
+    pub message: String,
+    /// The [`Diagnostic::code`].
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub code: Option<String>,
+    /// The [`Diagnostic::severity`], [`Severity::Error`] if it had none.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub severity: Severity,
+    /// The [`Diagnostic::help`] text.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub help: Option<String>,
+    /// The [`Diagnostic::note`] text.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub note: Option<String>,
+    /// The [`Diagnostic::url`].
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub url: Option<String>,
+    /// The name of the [`Diagnostic::source_code`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "filename", default, skip_serializing_if = "Option::is_none")
+    )]
+    pub source_name: Option<String>,
+    /// The [`Diagnostic::labels`], without sources of their own. Their
+    /// styles are not serialized.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub labels: Vec<LabeledSpan>,
+}
+
+impl DiagnosticSnapshot {
+    /// Copies `diagnostic`.
+    #[must_use]
+    pub fn new(diagnostic: &dyn Diagnostic) -> Self {
+        Self {
+            message: diagnostic.to_string(),
+            code: diagnostic.code().map(Cow::into_owned),
+            severity: diagnostic.severity().unwrap_or_default(),
+            help: diagnostic.help().map(Cow::into_owned),
+            note: diagnostic.note().map(Cow::into_owned),
+            url: diagnostic.url().map(Cow::into_owned),
+            source_name: diagnostic
+                .source_code()
+                .and_then(|source| source.name())
+                .map(String::from),
+            labels: diagnostic
+                .labels()
+                .iter()
+                .map(|label| {
+                    let text = label.label().map(String::from);
+                    let copy = LabeledSpan::new_with_kind(text, *label.inner(), label.kind());
+                    match label.style() {
+                        Some(style) => copy.with_style(style),
+                        None => copy,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&dyn Diagnostic> for DiagnosticSnapshot {
+    fn from(diagnostic: &dyn Diagnostic) -> Self {
+        Self::new(diagnostic)
+    }
+}
+
+impl fmt::Display for DiagnosticSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for DiagnosticSnapshot {}
+
+impl Diagnostic for DiagnosticSnapshot {
+    fn code(&self) -> Option<Cow<'_, str>> {
+        self.code.as_deref().map(Cow::Borrowed)
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        Some(self.severity)
+    }
+
+    fn help(&self) -> Option<Cow<'_, str>> {
+        self.help.as_deref().map(Cow::Borrowed)
+    }
+
+    fn note(&self) -> Option<Cow<'_, str>> {
+        self.note.as_deref().map(Cow::Borrowed)
+    }
+
+    fn url(&self) -> Option<Cow<'_, str>> {
+        self.url.as_deref().map(Cow::Borrowed)
+    }
+
+    fn labels(&self) -> &[LabeledSpan] {
+        &self.labels
+    }
+}
@@ -0,0 +1,109 @@
+//! Totals over a batch of diagnostics, for the line printed after them.
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{Diagnostic, Severity, SourceCode};
+
+/// Per-severity counts, affected files and most frequent codes of a batch of
+/// diagnostics.
+///
+/// Rendered by
+/// [`GraphicalReportHandler::render_summary`](crate::GraphicalReportHandler::render_summary)
+/// and [`JSONReportHandler::render_summary`](crate::JSONReportHandler::render_summary).
+///
+/// Only the diagnostics themselves are counted, not their related
+/// diagnostics. A file is a distinct [`SourceCode::name`] of a diagnostic's
+/// source code.
+///
+/// # Examples
+/// ```
+/// use miette::{Diagnostic, DiagnosticSummary, MietteDiagnostic, Severity};
+///
+/// let diagnostics = [
+///     MietteDiagnostic::new("unused variable").with_code("no-unused-vars"),
+///     MietteDiagnostic::new("unused import").with_code("no-unused-vars"),
+///     MietteDiagnostic::new("debugger").with_severity(Severity::Warning),
+/// ];
+/// let summary = DiagnosticSummary::new(diagnostics.iter().map(|d| d as &dyn Diagnostic))
+///     .with_top_codes(3);
+/// assert_eq!(summary.count(Severity::Error), 2);
+/// assert_eq!(summary.top_codes(), [(String::from("no-unused-vars"), 2)]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagnosticSummary {
+    errors: usize,
+    warnings: usize,
+    advice: usize,
+    files: usize,
+    /// Every code with its count, most frequent first.
+    codes: Vec<(String, usize)>,
+    top_codes: usize,
+}
+
+impl DiagnosticSummary {
+    /// Counts `diagnostics`. Diagnostics without a severity count as errors.
+    #[must_use]
+    pub fn new<'a>(diagnostics: impl IntoIterator<Item = &'a dyn Diagnostic>) -> Self {
+        let mut summary = Self::default();
+        let mut files = FxHashSet::default();
+        let mut codes = FxHashMap::<String, usize>::default();
+        for diagnostic in diagnostics {
+            match diagnostic.severity().unwrap_or_default() {
+                Severity::Error => summary.errors += 1,
+                Severity::Warning => summary.warnings += 1,
+                Severity::Advice => summary.advice += 1,
+            }
+            if let Some(name) = diagnostic.source_code().and_then(SourceCode::name) {
+                if !files.contains(name) {
+                    files.insert(name.to_owned());
+                }
+            }
+            if let Some(code) = diagnostic.code() {
+                *codes.entry(code.into_owned()).or_default() += 1;
+            }
+        }
+        summary.files = files.len();
+        summary.codes = codes.into_iter().collect();
+        summary.codes.sort_unstable_by(|(a, a_count), (b, b_count)| {
+            b_count.cmp(a_count).then_with(|| a.cmp(b))
+        });
+        summary
+    }
+
+    /// Lists the `n` most frequent codes after the totals. Codes with the
+    /// same count are listed alphabetically.
+    #[must_use]
+    pub const fn with_top_codes(mut self, n: usize) -> Self {
+        self.top_codes = n;
+        self
+    }
+
+    /// The number of diagnostics of `severity`.
+    #[must_use]
+    pub const fn count(&self, severity: Severity) -> usize {
+        match severity {
+            Severity::Error => self.errors,
+            Severity::Warning => self.warnings,
+            Severity::Advice => self.advice,
+        }
+    }
+
+    /// The number of diagnostics of any severity.
+    #[must_use]
+    pub const fn total(&self) -> usize {
+        self.errors + self.warnings + self.advice
+    }
+
+    /// The number of distinct files the diagnostics are in.
+    #[must_use]
+    pub const fn files(&self) -> usize {
+        self.files
+    }
+
+    /// The most frequent codes and their counts, at most as many as set with
+    /// [`DiagnosticSummary::with_top_codes`].
+    #[must_use]
+    pub fn top_codes(&self) -> &[(String, usize)] {
+        &self.codes[..self.top_codes.min(self.codes.len())]
+    }
+}
@@ -0,0 +1,438 @@
+//! A small JSON reader for the inputs this crate consumes, such as source
+//! maps. It accepts exactly the documents of [RFC 8259], rejecting numbers
+//! and escapes outside its grammar, and strings that escape half of a
+//! surrogate pair. Writing JSON is left to the renderers.
+//!
+//! [RFC 8259]: https://www.rfc-editor.org/rfc/rfc8259
+
+use std::fmt;
+
+/// Nesting deeper than this is rejected rather than recursed into.
+const MAX_DEPTH: usize = 128;
+
+/// A parsed JSON value. Objects keep their members in document order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// The member `key` of an object, or `None` for anything else.
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// The value as a non-negative integer that fits in `u64`.
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "the value is checked to be a whole number in range first"
+    )]
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Self::Number(n) if n >= 0.0 && n.fract() == 0.0 && n < 18_446_744_073_709_551_616.0 => {
+                Some(n as u64)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Where and why a JSON document failed to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The byte offset of the offending input.
+    pub offset: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+/// Parses a complete JSON document.
+pub fn parse(input: &str) -> Result<Value, ParseError> {
+    let mut parser = Parser { input: input.as_bytes(), pos: 0 };
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.pos < parser.input.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    const fn error(&self, message: &'static str) -> ParseError {
+        ParseError { offset: self.pos, message }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.input.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.input.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8, message: &'static str) -> Result<(), ParseError> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, ParseError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+        match self.peek() {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, ParseError> {
+        if self.input[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("expected a value"))
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value, ParseError> {
+        self.pos += 1;
+        let mut members = Vec::new();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(members));
+        }
+        loop {
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected a string key"));
+            }
+            let key = self.string()?;
+            self.expect(b':', "expected `:`")?;
+            members.push((key, self.value(depth + 1)?));
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(members));
+                }
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value, ParseError> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(items));
+                }
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    /// Consumes `byte` if it is next, without skipping whitespace.
+    fn eat(&mut self, byte: u8) -> bool {
+        let found = self.input.get(self.pos) == Some(&byte);
+        self.pos += usize::from(found);
+        found
+    }
+
+    /// Consumes a run of digits, returning how many there were.
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while self.input.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    /// A number: an optional `-`, an integer part without leading zeros,
+    /// then an optional fraction and exponent, each with at least one digit.
+    /// Numbers too large for an `f64` are rejected.
+    fn number(&mut self) -> Result<Value, ParseError> {
+        let start = self.pos;
+        let invalid = ParseError { offset: start, message: "invalid number" };
+        self.eat(b'-');
+        if self.eat(b'0') {
+            if self.input.get(self.pos).is_some_and(u8::is_ascii_digit) {
+                return Err(invalid);
+            }
+        } else if self.digits() == 0 {
+            return Err(invalid);
+        }
+        if self.eat(b'.') && self.digits() == 0 {
+            return Err(invalid);
+        }
+        if self.eat(b'e') || self.eat(b'E') {
+            let _ = self.eat(b'+') || self.eat(b'-');
+            if self.digits() == 0 {
+                return Err(invalid);
+            }
+        }
+        // The scanned bytes are ASCII, so this cannot fail.
+        let text = std::str::from_utf8(&self.input[start..self.pos]).unwrap_or_default();
+        match text.parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(Value::Number(n)),
+            _ => Err(ParseError { offset: start, message: "number out of range" }),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.pos += 1;
+        let mut out = Vec::new();
+        loop {
+            let Some(&byte) = self.input.get(self.pos) else {
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escape = self.input.get(self.pos).copied();
+                    self.pos += 1;
+                    let c = match escape {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{08}',
+                        Some(b'f') => '\u{0c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => self.unicode_escape(self.pos - 2)?,
+                        _ => {
+                            return Err(ParseError {
+                                offset: self.pos - 2,
+                                message: "invalid escape",
+                            });
+                        }
+                    };
+                    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                0..0x20 => return Err(self.error("control character in string")),
+                _ => out.push(byte),
+            }
+        }
+        // The input is a `str` and escapes push whole characters.
+        String::from_utf8(out).map_err(|_| self.error("invalid UTF-8"))
+    }
+
+    /// Reads the four hex digits after the `\u` at `start`, plus the
+    /// escaped low half of a surrogate pair. Unpaired surrogates are
+    /// rejected, as no `char` holds them.
+    fn unicode_escape(&mut self, start: usize) -> Result<char, ParseError> {
+        let unpaired = ParseError { offset: start, message: "unpaired surrogate" };
+        let high = self.hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or(unpaired);
+        }
+        if !self.input[self.pos..].starts_with(b"\\u") {
+            return Err(unpaired);
+        }
+        self.pos += 2;
+        let low = self.hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(unpaired);
+        }
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).ok_or(unpaired)
+    }
+
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        let digits = self.input.get(self.pos..self.pos + 4).unwrap_or_default();
+        if digits.len() != 4 || !digits.iter().all(u8::is_ascii_hexdigit) {
+            return Err(self.error("invalid escape"));
+        }
+        self.pos += 4;
+        Ok(digits.iter().fold(0, |value, &digit| {
+            // `is_ascii_hexdigit` above guarantees a digit.
+            value * 16 + char::from(digit).to_digit(16).unwrap_or_default()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_values() {
+        let value = parse(r#" {"a": [1, -2.5e1, true, null], "b": {"c": "d"}} "#).unwrap();
+        assert_eq!(
+            value.get("a").and_then(Value::as_array),
+            Some(&[Value::Number(1.0), Value::Number(-25.0), Value::Bool(true), Value::Null][..])
+        );
+        assert_eq!(value.get("b").and_then(|b| b.get("c")).and_then(Value::as_str), Some("d"));
+        assert_eq!(value.get("a").and_then(|a| a.as_array()?[0].as_u64()), Some(1));
+    }
+
+    #[test]
+    fn decodes_string_escapes() {
+        let value = parse(r#""\"\\\/\b\f\n\r\t é 🦀 🦀""#).unwrap();
+        assert_eq!(value.as_str(), Some("\"\\/\u{08}\u{0c}\n\r\t é 🦀 🦀"));
+    }
+
+    #[test]
+    fn reports_error_offsets() {
+        let error = |input| parse(input).unwrap_err();
+        assert_eq!(error("[1,]"), ParseError { offset: 3, message: "expected a value" });
+        assert_eq!(error(r#"{"a" 1}"#), ParseError { offset: 5, message: "expected `:`" });
+        assert_eq!(error(r#""\x""#), ParseError { offset: 1, message: "invalid escape" });
+        assert_eq!(error("[1] 2"), ParseError { offset: 4, message: "trailing characters" });
+        assert_eq!(error("-"), ParseError { offset: 0, message: "invalid number" });
+        assert_eq!(error(r#"["\ud800"]"#), ParseError { offset: 2, message: "unpaired surrogate" });
+        assert_eq!(error(&"[".repeat(200)).message, "nesting too deep");
+    }
+
+    #[test]
+    fn accepts_rfc_8259_numbers() {
+        for (input, expected) in [
+            ("0", 0.0),
+            ("-0", -0.0),
+            ("10", 10.0),
+            ("-1.5", -1.5),
+            ("0.25", 0.25),
+            ("1e2", 100.0),
+            ("1E+2", 100.0),
+            ("25e-1", 2.5),
+            ("0.5E0", 0.5),
+        ] {
+            assert_eq!(parse(input), Ok(Value::Number(expected)), "{input}");
+        }
+    }
+
+    #[test]
+    fn rejects_numbers_outside_rfc_8259() {
+        let inputs = "01 -01 00 1. -.5 .5 +1 --1 1e 1e+ 1.e5 0x10 1_000 NaN Infinity -Infinity \
+                      1e999 -1e999";
+        for input in inputs.split_whitespace() {
+            assert!(parse(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn rejects_escapes_outside_rfc_8259() {
+        for input in [
+            r#""\a""#,
+            r#""\'""#,
+            r#""\u12""#,
+            r#""\u12g4""#,
+            r#""\U0041""#,
+            r#""\ud800""#,
+            r#""\ud800x""#,
+            r#""\ud800\n""#,
+            r#""\ud800A""#,
+            r#""\ud800\ud800""#,
+            r#""\udc00""#,
+            r#""\udc00\ud800""#,
+            "\"tab\there\"",
+            "\"\u{0}\"",
+        ] {
+            assert!(parse(input).is_err(), "{input}");
+        }
+    }
+
+    /// Converts a `serde_json` value to ours, to compare the two parsers.
+    fn from_serde(value: serde_json::Value) -> Value {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(b),
+            serde_json::Value::Number(n) => Value::Number(n.as_f64().unwrap()),
+            serde_json::Value::String(s) => Value::String(s),
+            serde_json::Value::Array(items) => {
+                Value::Array(items.into_iter().map(from_serde).collect())
+            }
+            serde_json::Value::Object(members) => {
+                Value::Object(members.into_iter().map(|(k, v)| (k, from_serde(v))).collect())
+            }
+        }
+    }
+
+    /// Random short documents from JSON's own alphabet parse, or fail to,
+    /// exactly as they do with `serde_json`.
+    #[test]
+    fn matches_serde_json() {
+        const PIECES: &[&str] = &[
+            "0", "1", "9", "-", "+", ".", "e", "E", "[", "]", "{", "}", ",", ":", " ", "\"", "\\",
+            "u", "d83e", "dd80", "00e9", "n", "t", "a", "true", "false", "null", "\"k\"",
+        ];
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = |n: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            usize::try_from(state % n as u64).unwrap()
+        };
+        for _ in 0..50_000 {
+            let len = 1 + next(8);
+            let input: String =
+                std::iter::repeat_with(|| PIECES[next(PIECES.len())]).take(len).collect();
+            // `serde_json` sorts object members and keeps the last of
+            // duplicate keys, so only documents without objects are
+            // compared by value.
+            let ours = parse(&input);
+            let theirs = serde_json::from_str::<serde_json::Value>(&input);
+            assert_eq!(ours.is_ok(), theirs.is_ok(), "{input:?}: {ours:?}");
+            if let (Ok(ours), Ok(theirs)) = (ours, theirs) {
+                if !input.contains('{') {
+                    assert_eq!(ours, from_serde(theirs), "{input:?}");
+                }
+            }
+        }
+    }
+}
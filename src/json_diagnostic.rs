@@ -0,0 +1,404 @@
+//! Reading [`JSONReportHandler`](crate::JSONReportHandler) output back into
+//! diagnostics.
+
+use std::{borrow::Cow, error::Error, fmt, sync::Arc};
+
+use crate::{
+    Applicability, Diagnostic, LabelKind, LabeledSpan, Severity, SourceCode, SourceSpan,
+    Suggestion,
+    json::{self, Value},
+};
+
+/// A diagnostic read back from the output of
+/// [`JSONReportHandler::render_report`](crate::JSONReportHandler::render_report),
+/// for replaying cached or uploaded reports.
+///
+/// JSON reports do not carry source text, so the sources are looked up by
+/// file name while parsing. With the same sources, the diagnostic renders
+/// the same as the original through both renderers. The exception is a
+/// cause that was itself a [`Diagnostic`]: only its message is in the
+/// report, so it comes back as a plain error without its code or snippets.
+///
+/// Spans in a report are offsets in the whole file. They are made relative
+/// to the source they are rendered against, so they match the diagnostic's
+/// own labels when that source is a fragment of the file, whether it is
+/// found while parsing or attached later.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+///
+/// use miette::{
+///     Diagnostic, JSONDiagnostic, JSONReportHandler, LabeledSpan, MietteDiagnostic, NamedSource,
+///     SourceCode,
+/// };
+///
+/// let source: Arc<dyn SourceCode> = Arc::new(NamedSource::new("test.js", "let ? = 1;"));
+/// let diagnostic = MietteDiagnostic::new("unexpected token")
+///     .with_label(LabeledSpan::at(4..5, "here"))
+///     .with_source_code(Arc::clone(&source));
+/// let mut json = String::new();
+/// JSONReportHandler::new().render_report(&mut json, &diagnostic).unwrap();
+///
+/// let parsed = JSONDiagnostic::from_json_with_sources(&json, |name| {
+///     (name == "test.js").then(|| Arc::clone(&source))
+/// })
+/// .unwrap();
+/// assert_eq!(parsed.to_string(), "unexpected token");
+/// assert_eq!(parsed.labels(), diagnostic.labels());
+/// assert_eq!(parsed.source_code().and_then(SourceCode::name), Some("test.js"));
+/// ```
+pub struct JSONDiagnostic {
+    message: String,
+    code: Option<String>,
+    severity: Option<Severity>,
+    help: Option<String>,
+    note: Option<String>,
+    url: Option<String>,
+    filename: Option<String>,
+    labels: Vec<LabeledSpan>,
+    suggestions: Vec<Suggestion>,
+    cause: Option<Box<Cause>>,
+    related: Vec<Box<dyn Diagnostic + Send + Sync>>,
+    source_code: Option<Arc<dyn SourceCode>>,
+    /// Where [`Self::source_code`] starts in its file, which the spans of
+    /// labels without a source of their own, and of suggestions, are
+    /// relative to.
+    origin: u32,
+}
+
+/// One message of the cause chain, linked to the next.
+#[derive(Debug)]
+struct Cause {
+    message: String,
+    source: Option<Box<Cause>>,
+}
+
+impl fmt::Display for Cause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for Cause {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|source| source as &(dyn Error + 'static))
+    }
+}
+
+/// Why [`JSONDiagnostic::from_json`] could not read a report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JSONReportError {
+    /// The report is not valid JSON.
+    Json {
+        /// The byte offset of the error in the report.
+        offset: usize,
+        /// What was wrong.
+        message: &'static str,
+    },
+    /// A field is missing or has a value `JSONReportHandler` does not write.
+    InvalidField(&'static str),
+}
+
+impl fmt::Display for JSONReportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json { offset, message } => {
+                write!(f, "report is not valid JSON: {message} at byte {offset}")
+            }
+            Self::InvalidField(field) => write!(f, "report has an invalid `{field}` field"),
+        }
+    }
+}
+
+impl Error for JSONReportError {}
+
+impl JSONDiagnostic {
+    /// Reads a report without any source code. Labels keep their spans, but
+    /// no snippets are rendered until a source is attached with
+    /// [`JSONDiagnostic::with_source_code`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when `json` is not a report written by
+    /// [`JSONReportHandler`](crate::JSONReportHandler).
+    pub fn from_json(json: &str) -> Result<Self, JSONReportError> {
+        Self::from_json_with_sources(json, |_| None)
+    }
+
+    /// Reads a report, calling `sources` with each file name it mentions to
+    /// get the source code to render that file's labels against.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when `json` is not a report written by
+    /// [`JSONReportHandler`](crate::JSONReportHandler).
+    pub fn from_json_with_sources(
+        json: &str,
+        mut sources: impl FnMut(&str) -> Option<Arc<dyn SourceCode>>,
+    ) -> Result<Self, JSONReportError> {
+        let value = json::parse(json).map_err(|error| JSONReportError::Json {
+            offset: error.offset,
+            message: error.message,
+        })?;
+        Self::from_value(&value, &mut sources)
+    }
+
+    /// Renders labels against `source_code`, as if it were the source of the
+    /// original diagnostic. When it is a fragment of a larger file, spans are
+    /// moved to be relative to where it starts; spans starting before it are
+    /// moved to its start.
+    #[must_use]
+    pub fn with_source_code(mut self, source_code: Arc<dyn SourceCode>) -> Self {
+        let origin = source_code.origin().offset;
+        let rebase = |span: &SourceSpan| {
+            let offset = (u64::from(span.offset()) + u64::from(self.origin))
+                .saturating_sub(u64::from(origin));
+            u32::try_from(offset).unwrap_or(u32::MAX)
+        };
+        for label in &mut self.labels {
+            if label.source_code().is_none() {
+                label.set_span_offset(rebase(label.inner()));
+            }
+        }
+        for suggestion in &mut self.suggestions {
+            let mut rebased = Suggestion::new(
+                (rebase(suggestion.inner()), suggestion.inner().len()),
+                suggestion.replacement(),
+            )
+            .with_applicability(suggestion.applicability());
+            if let Some(message) = suggestion.message() {
+                rebased = rebased.with_message(message);
+            }
+            *suggestion = rebased;
+        }
+        self.source_code = Some(source_code);
+        self.origin = origin;
+        self
+    }
+
+    /// The name of the source the report was rendered against.
+    #[must_use]
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    fn from_value(
+        value: &Value,
+        sources: &mut dyn FnMut(&str) -> Option<Arc<dyn SourceCode>>,
+    ) -> Result<Self, JSONReportError> {
+        let message = string(value, "message")?.ok_or(JSONReportError::InvalidField("message"))?;
+        let severity = string(value, "severity")?
+            .map(|name| {
+                [Severity::Error, Severity::Warning, Severity::Advice]
+                    .into_iter()
+                    .find(|severity| severity_name(*severity) == name)
+                    .ok_or(JSONReportError::InvalidField("severity"))
+            })
+            .transpose()?;
+        let filename = string(value, "filename")?;
+        let source_code = filename.as_deref().and_then(&mut *sources);
+
+        let mut cause = None;
+        for message in array(value, "causes")?.iter().rev() {
+            let message = message.as_str().ok_or(JSONReportError::InvalidField("causes"))?;
+            cause = Some(Box::new(Cause { message: message.into(), source: cause }));
+        }
+
+        let labels = array(value, "labels")?
+            .iter()
+            .map(|label| {
+                let kind = match string(label, "kind")? {
+                    Some(name) => [
+                        LabelKind::Primary,
+                        LabelKind::Secondary,
+                        LabelKind::Note,
+                        LabelKind::Added,
+                        LabelKind::Removed,
+                    ]
+                    .into_iter()
+                    .find(|kind| kind.as_str() == name)
+                    .ok_or(JSONReportError::InvalidField("kind"))?,
+                    None => LabelKind::default(),
+                };
+                // Labels in the diagnostic's own file are rendered against it.
+                let label_source = match string(label, "filename")? {
+                    Some(name) if filename.as_deref() != Some(name.as_str()) => sources(&name),
+                    _ => None,
+                };
+                let origin = label_source.as_deref().or(source_code.as_deref());
+                let parsed =
+                    LabeledSpan::new_with_kind(string(label, "label")?, span(label, origin)?, kind);
+                Ok(match label_source {
+                    Some(source) => parsed.with_source_code(source),
+                    None => parsed,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let suggestions = array(value, "suggestions")?
+            .iter()
+            .map(|suggestion| {
+                let replacement = string(suggestion, "replacement")?
+                    .ok_or(JSONReportError::InvalidField("replacement"))?;
+                let applicability = match string(suggestion, "applicability")? {
+                    Some(name) => [
+                        Applicability::MachineApplicable,
+                        Applicability::MaybeIncorrect,
+                        Applicability::HasPlaceholders,
+                        Applicability::Unspecified,
+                    ]
+                    .into_iter()
+                    .find(|applicability| applicability.as_str() == name)
+                    .ok_or(JSONReportError::InvalidField("applicability"))?,
+                    None => Applicability::default(),
+                };
+                let parsed =
+                    Suggestion::new(span(suggestion, source_code.as_deref())?, replacement)
+                        .with_applicability(applicability);
+                Ok(match string(suggestion, "message")? {
+                    Some(message) => parsed.with_message(message),
+                    None => parsed,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let related = array(value, "related")?
+            .iter()
+            .map(|related| {
+                let related: Box<dyn Diagnostic + Send + Sync> =
+                    Box::new(Self::from_value(related, sources)?);
+                Ok(related)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            message,
+            code: string(value, "code")?,
+            severity,
+            help: string(value, "help")?,
+            note: string(value, "note")?,
+            url: string(value, "url")?,
+            filename,
+            labels,
+            suggestions,
+            cause,
+            related,
+            origin: source_code.as_deref().map_or(0, |source| source.origin().offset),
+            source_code,
+        })
+    }
+}
+
+/// The name `JSONReportHandler` writes for `severity`.
+const fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Advice => "advice",
+    }
+}
+
+/// The optional string member `key` of `value`.
+fn string(value: &Value, key: &'static str) -> Result<Option<String>, JSONReportError> {
+    match value.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(_) => Err(JSONReportError::InvalidField(key)),
+    }
+}
+
+/// The optional array member `key` of `value`, empty when missing.
+fn array<'a>(value: &'a Value, key: &'static str) -> Result<&'a [Value], JSONReportError> {
+    match value.get(key) {
+        None | Some(Value::Null) => Ok(&[]),
+        Some(items) => items.as_array().ok_or(JSONReportError::InvalidField(key)),
+    }
+}
+
+/// The `span` member of a label or suggestion, made relative to `source`
+/// again if it is a fragment of a larger file. Its line and column are
+/// derived from the source, so they are not read.
+fn span(value: &Value, source: Option<&dyn SourceCode>) -> Result<SourceSpan, JSONReportError> {
+    let span = value.get("span").ok_or(JSONReportError::InvalidField("span"))?;
+    let field = |key| {
+        span.get(key)
+            .and_then(Value::as_u64)
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or(JSONReportError::InvalidField(key))
+    };
+    let origin = source.map_or(0, |source| source.origin().offset);
+    let offset =
+        field("offset")?.checked_sub(origin).ok_or(JSONReportError::InvalidField("offset"))?;
+    Ok(SourceSpan::from((offset, field("length")?)))
+}
+
+impl fmt::Debug for JSONDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JSONDiagnostic")
+            .field("message", &self.message)
+            .field("code", &self.code)
+            .field("severity", &self.severity)
+            .field("help", &self.help)
+            .field("note", &self.note)
+            .field("url", &self.url)
+            .field("filename", &self.filename)
+            .field("labels", &self.labels)
+            .field("suggestions", &self.suggestions)
+            .field("cause", &self.cause)
+            .field("related", &self.related)
+            .field("source_code", &self.source_code.as_ref().map(|source| source.name()))
+            .field("origin", &self.origin)
+            .finish()
+    }
+}
+
+impl fmt::Display for JSONDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for JSONDiagnostic {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause.as_deref().map(|cause| cause as &(dyn Error + 'static))
+    }
+}
+
+impl Diagnostic for JSONDiagnostic {
+    fn code(&self) -> Option<Cow<'_, str>> {
+        self.code.as_deref().map(Cow::Borrowed)
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        self.severity
+    }
+
+    fn help(&self) -> Option<Cow<'_, str>> {
+        self.help.as_deref().map(Cow::Borrowed)
+    }
+
+    fn note(&self) -> Option<Cow<'_, str>> {
+        self.note.as_deref().map(Cow::Borrowed)
+    }
+
+    fn url(&self) -> Option<Cow<'_, str>> {
+        self.url.as_deref().map(Cow::Borrowed)
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.source_code.as_deref()
+    }
+
+    fn labels(&self) -> &[LabeledSpan] {
+        &self.labels
+    }
+
+    fn related(&self) -> &[Box<dyn Diagnostic + Send + Sync>] {
+        &self.related
+    }
+
+    fn suggestions(&self) -> &[Suggestion] {
+        &self.suggestions
+    }
+}
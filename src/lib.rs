@@ -3,12 +3,33 @@
 //! This crate defines the [`Diagnostic`] and [`SourceCode`] protocols together
 //! with graphical and JSON renderers. It intentionally does not provide an
 //! application error container: callers own diagnostics directly or through
-//! boxed trait objects and choose a renderer explicitly.
+//! boxed trait objects and choose a renderer explicitly. [`MietteDiagnostic`]
+//! covers the common case of reporting without a dedicated error type.
+//! With the `derive` feature, `#[derive(Diagnostic)]` implements the protocol
+//! for your own error types from attributes. With the `serde` feature, the
+//! protocol types and [`DiagnosticSnapshot`] implement `Serialize` and
+//! `Deserialize`.
 
+pub use diagnostic_set::DiagnosticSet;
+pub use diagnostic_snapshot::DiagnosticSnapshot;
+pub use diagnostic_summary::DiagnosticSummary;
+pub use json_diagnostic::{JSONDiagnostic, JSONReportError};
+#[cfg(feature = "derive")]
+pub use miette_derive::Diagnostic;
+pub use miette_diagnostic::MietteDiagnostic;
 pub use protocol::*;
 pub use renderers::*;
-pub use source::NamedSource;
+pub use source::{
+    ColumnUnit, FileSource, LineColumn, MappedSource, NamedSource, SourceFragment, SourceIndex,
+    SourceMapError, SourceOrigin,
+};
 
+mod diagnostic_set;
+mod diagnostic_snapshot;
+mod diagnostic_summary;
+mod json;
+mod json_diagnostic;
+mod miette_diagnostic;
 mod protocol;
 mod renderers;
 mod source;
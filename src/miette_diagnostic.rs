@@ -0,0 +1,187 @@
+//! An owned [`Diagnostic`] that can be built without writing a new type.
+
+use std::{borrow::Cow, error::Error, fmt, sync::Arc};
+
+use crate::{Diagnostic, LabeledSpan, Severity, SourceCode, source::same_source};
+
+/// Owned diagnostic built with a fluent API, for reporting without defining
+/// a dedicated error type.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+///
+/// use miette::{Diagnostic, LabeledSpan, MietteDiagnostic, NamedSource, Severity};
+///
+/// let diagnostic = MietteDiagnostic::new("unexpected token")
+///     .with_code("parser::unexpected")
+///     .with_severity(Severity::Warning)
+///     .with_help("remove it")
+///     .with_label(LabeledSpan::at(4..5, "here"))
+///     .with_source_code(Arc::new(NamedSource::new("test.js", "let ? = 1;")));
+/// assert_eq!(diagnostic.to_string(), "unexpected token");
+/// assert_eq!(diagnostic.code().as_deref(), Some("parser::unexpected"));
+/// ```
+#[derive(Clone)]
+pub struct MietteDiagnostic {
+    message: String,
+    code: Option<String>,
+    severity: Option<Severity>,
+    help: Option<String>,
+    note: Option<String>,
+    url: Option<String>,
+    labels: Vec<LabeledSpan>,
+    source_code: Option<Arc<dyn SourceCode>>,
+}
+
+impl MietteDiagnostic {
+    /// Creates a diagnostic with the given message and nothing else.
+    #[must_use]
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            code: None,
+            severity: None,
+            help: None,
+            note: None,
+            url: None,
+            labels: Vec::new(),
+            source_code: None,
+        }
+    }
+
+    /// Sets the [`Diagnostic::code`].
+    #[must_use]
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Sets the [`Diagnostic::severity`].
+    #[must_use]
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    /// Sets the [`Diagnostic::help`] text.
+    #[must_use]
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// Sets the [`Diagnostic::note`] text.
+    #[must_use]
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    /// Sets the [`Diagnostic::url`].
+    #[must_use]
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Adds a label to the [`Diagnostic::labels`].
+    #[must_use]
+    pub fn with_label(mut self, label: LabeledSpan) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    /// Adds several labels to the [`Diagnostic::labels`].
+    #[must_use]
+    pub fn with_labels(mut self, labels: impl IntoIterator<Item = LabeledSpan>) -> Self {
+        self.labels.extend(labels);
+        self
+    }
+
+    /// Sets the [`Diagnostic::source_code`] the labels point into.
+    #[must_use]
+    pub fn with_source_code(mut self, source_code: Arc<dyn SourceCode>) -> Self {
+        self.source_code = Some(source_code);
+        self
+    }
+
+    /// The diagnostic's message.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Debug for MietteDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MietteDiagnostic")
+            .field("message", &self.message)
+            .field("code", &self.code)
+            .field("severity", &self.severity)
+            .field("help", &self.help)
+            .field("note", &self.note)
+            .field("url", &self.url)
+            .field("labels", &self.labels)
+            .field("source_code", &self.source_code.as_ref().map(|source| source.name()))
+            .finish()
+    }
+}
+
+/// Source code compares by identity first and then by name and contents.
+impl PartialEq for MietteDiagnostic {
+    fn eq(&self, other: &Self) -> bool {
+        self.message == other.message
+            && self.code == other.code
+            && self.severity == other.severity
+            && self.help == other.help
+            && self.note == other.note
+            && self.url == other.url
+            && self.labels == other.labels
+            && match (&self.source_code, &other.source_code) {
+                (None, None) => true,
+                (Some(a), Some(b)) => same_source(&**a, &**b),
+                _ => false,
+            }
+    }
+}
+
+impl Eq for MietteDiagnostic {}
+
+impl fmt::Display for MietteDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for MietteDiagnostic {}
+
+impl Diagnostic for MietteDiagnostic {
+    fn code(&self) -> Option<Cow<'_, str>> {
+        self.code.as_deref().map(Cow::Borrowed)
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        self.severity
+    }
+
+    fn help(&self) -> Option<Cow<'_, str>> {
+        self.help.as_deref().map(Cow::Borrowed)
+    }
+
+    fn note(&self) -> Option<Cow<'_, str>> {
+        self.note.as_deref().map(Cow::Borrowed)
+    }
+
+    fn url(&self) -> Option<Cow<'_, str>> {
+        self.url.as_deref().map(Cow::Borrowed)
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.source_code.as_deref()
+    }
+
+    fn labels(&self) -> &[LabeledSpan] {
+        &self.labels
+    }
+}
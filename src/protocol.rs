@@ -3,7 +3,18 @@ This module defines the core of the miette protocol: a series of types and
 traits that you can implement to get access to miette's (and related library's)
 full reporting and such features.
 */
-use std::{borrow::Cow, error::Error, ops::Range};
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt,
+    num::TryFromIntError,
+    ops::Range,
+    sync::{Arc, OnceLock},
+};
+
+use owo_colors::Style;
+
+use crate::{ColumnUnit, LineColumn, SourceIndex, SourceOrigin, source::same_source};
 
 /// Adds rich metadata to your Error that can be used by
 /// Rich metadata that renderers use to produce human-friendly error messages.
@@ -56,13 +67,44 @@ pub trait Diagnostic: Error {
     fn labels(&self) -> &[LabeledSpan] {
         &[]
     }
+
+    /// Additional `Diagnostic`s related to this one, rendered as nested
+    /// reports beneath it. Each keeps its own severity, code, help and labels.
+    ///
+    /// A related diagnostic without a [`Diagnostic::source_code`] of its own
+    /// is rendered against this diagnostic's source code.
+    fn related(&self) -> &[Box<dyn Diagnostic + Send + Sync>] {
+        &[]
+    }
+
+    /// The cause of this `Diagnostic`, if that cause is itself a
+    /// `Diagnostic`. Renderers prefer this over [`Error::source`] when walking
+    /// the cause chain, so the cause's own snippets and labels are rendered.
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        None
+    }
+
+    /// Replacement edits that fix this `Diagnostic`, applied to its
+    /// [`Diagnostic::source_code`]. Editors and `--fix` tooling should only
+    /// apply [`Applicability::MachineApplicable`] suggestions automatically.
+    ///
+    /// Unlike labels, suggestions are never passed through
+    /// [`SourceCode::remap_label`]: their replacement text is written for the
+    /// source they were computed on, so on a
+    /// [`MappedSource`](crate::MappedSource) they are shown and reported
+    /// against the generated code, not the original.
+    fn suggestions(&self) -> &[Suggestion] {
+        &[]
+    }
 }
 
 /**
 [`Diagnostic`] severity. Renderers use this to change the way diagnostics are
 displayed. Defaults to [`Severity::Error`].
 */
-#[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Default)]
+#[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum Severity {
     /// Just some help. Here's how you could be doing it better.
     Advice,
@@ -83,33 +125,219 @@ pub trait SourceCode: Send + Sync {
     fn name(&self) -> Option<&str> {
         None
     }
+
+    /// Whether the source could be loaded. Renderers leave out the snippets
+    /// of a source that is not available instead of failing the report.
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// Where a label pointing into this source should be shown instead, or
+    /// `None` to show it as it is. Renderers call this for every label
+    /// without a source of its own. Sources over generated code, such as
+    /// [`MappedSource`](crate::MappedSource), use it to point labels back
+    /// into the original source.
+    fn remap_label(&self, label: &LabeledSpan) -> Option<LabeledSpan> {
+        let _ = label;
+        None
+    }
+
+    /// Where this source starts in the file it was taken from. Renderers
+    /// report positions in that file for fragments such as
+    /// [`SourceFragment`](crate::SourceFragment); whole files start at the
+    /// default, the start of the file.
+    fn origin(&self) -> SourceOrigin {
+        SourceOrigin::default()
+    }
+
+    /// The offset each line of [`SourceCode::data`] starts at, for sources
+    /// that already keep such a table, such as a language server's open
+    /// documents. Renderers then look lines up with a binary search instead
+    /// of scanning the source for line breaks.
+    ///
+    /// The table must be exactly what
+    /// [`SourceIndex`](crate::SourceIndex) computes: `0`, then the offset
+    /// after every `\n`, `\r\n` or lone `\r`, so it ends with the length of
+    /// the source when the source ends with a line break. Tables that do not
+    /// start at `0`, are not strictly increasing, or have a start that is not
+    /// right after a line break are ignored, and the source is scanned
+    /// instead. A table that leaves a line out is not caught, and puts
+    /// positions on the wrong lines.
+    fn line_starts(&self) -> Option<&[usize]> {
+        None
+    }
 }
 
 /// A labeled [`SourceSpan`].
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// The span points into the diagnostic's [`Diagnostic::source_code`] unless
+/// the label carries a source of its own (see
+/// [`LabeledSpan::with_source_code`]).
+///
+/// With the `serde` feature, the style and source of a label are not
+/// serialized.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LabeledSpan {
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
     label: Option<String>,
     span: SourceSpan,
-    primary: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    kind: LabelKind,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    style: Option<Style>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    source_code: Option<Arc<dyn SourceCode>>,
+}
+
+/// The role a [`LabeledSpan`] plays in a diagnostic, which decides how the
+/// graphical renderer draws it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum LabelKind {
+    /// The span the diagnostic is about. Its location is shown in the
+    /// snippet header.
+    Primary,
+    /// A span that explains the primary one, such as "defined here".
+    #[default]
+    Secondary,
+    /// Surrounding context that is not itself a problem.
+    Note,
+    /// Text that was inserted.
+    Added,
+    /// Text that was removed.
+    Removed,
+}
+
+impl LabelKind {
+    /// The kebab-case name used by machine-readable renderers.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Primary => "primary",
+            Self::Secondary => "secondary",
+            Self::Note => "note",
+            Self::Added => "added",
+            Self::Removed => "removed",
+        }
+    }
+}
+
+impl fmt::Debug for LabeledSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("LabeledSpan");
+        debug.field("label", &self.label).field("span", &self.span).field("kind", &self.kind);
+        if let Some(style) = &self.style {
+            debug.field("style", style);
+        }
+        if let Some(source_code) = &self.source_code {
+            debug.field("source_code", &source_code.name());
+        }
+        debug.finish()
+    }
 }
 
+/// Sources compare by identity first and then by name and contents, so two
+/// labels built from separately loaded copies of a file are still equal.
+impl PartialEq for LabeledSpan {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label
+            && self.span == other.span
+            && self.kind == other.kind
+            && self.style == other.style
+            && match (&self.source_code, &other.source_code) {
+                (None, None) => true,
+                (Some(a), Some(b)) => same_source(&**a, &**b),
+                _ => false,
+            }
+    }
+}
+
+impl Eq for LabeledSpan {}
+
 impl LabeledSpan {
     /// Makes a new labeled span.
     #[must_use]
     pub const fn new(label: Option<String>, offset: u32, len: u32) -> Self {
-        Self { label, span: SourceSpan { offset, length: len }, primary: false }
+        Self {
+            label,
+            span: SourceSpan { offset, length: len },
+            kind: LabelKind::Secondary,
+            style: None,
+            source_code: None,
+        }
     }
 
     /// Makes a new labeled span using an existing span.
     #[must_use]
     pub fn new_with_span(label: Option<String>, span: impl Into<SourceSpan>) -> Self {
-        Self { label, span: span.into(), primary: false }
+        Self::new_with_kind(label, span, LabelKind::Secondary)
     }
 
     /// Makes a new labeled primary span using an existing span.
     #[must_use]
     pub fn new_primary_with_span(label: Option<String>, span: impl Into<SourceSpan>) -> Self {
-        Self { label, span: span.into(), primary: true }
+        Self::new_with_kind(label, span, LabelKind::Primary)
+    }
+
+    /// Makes a new labeled span from 0-based line/column positions, for
+    /// tools that only know positions. Returns `None` if either position is
+    /// not in the indexed source or `end` comes before `start`.
+    ///
+    /// # Examples
+    /// ```
+    /// use miette::{ColumnUnit, LabeledSpan, LineColumn, SourceIndex};
+    ///
+    /// let source = "let a = 1;\nlet 🦀 = 2;";
+    /// let index = SourceIndex::new(&source);
+    /// let (start, end) = (LineColumn::new(1, 4), LineColumn::new(1, 6));
+    /// let label = LabeledSpan::from_line_col(None, &index, start, end, ColumnUnit::Utf16);
+    /// assert_eq!(label, Some(LabeledSpan::new(None, 15, 4)));
+    /// ```
+    #[must_use]
+    pub fn from_line_col(
+        label: Option<String>,
+        index: &SourceIndex<'_>,
+        start: LineColumn,
+        end: LineColumn,
+        unit: ColumnUnit,
+    ) -> Option<Self> {
+        Some(Self::new_with_span(label, index.span(start, end, unit)?))
+    }
+
+    /// Makes a new labeled span of the given [`LabelKind`].
+    ///
+    /// # Examples
+    /// ```
+    /// use miette::{LabelKind, LabeledSpan};
+    ///
+    /// let label = LabeledSpan::new_with_kind(Some("defined here".into()), 4..7, LabelKind::Note);
+    /// assert_eq!(label.kind(), LabelKind::Note);
+    /// assert!(!label.primary());
+    /// ```
+    #[must_use]
+    pub fn new_with_kind(
+        label: Option<String>,
+        span: impl Into<SourceSpan>,
+        kind: LabelKind,
+    ) -> Self {
+        Self { label, span: span.into(), kind, style: None, source_code: None }
+    }
+
+    /// Changes the [`LabelKind`] of this label.
+    #[must_use]
+    pub const fn with_kind(mut self, kind: LabelKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Draws this label in `style` instead of the theme's style for its
+    /// [`LabelKind`].
+    #[must_use]
+    pub const fn with_style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
     }
 
     /// Change the offset of the span.
@@ -117,6 +345,106 @@ impl LabeledSpan {
         self.span.offset = offset;
     }
 
+    /// The offset one past the end of the span. See [`SourceSpan::end`].
+    #[must_use]
+    pub const fn end(&self) -> u32 {
+        self.span.end()
+    }
+
+    /// The span as a byte range. See [`SourceSpan::to_range`].
+    #[must_use]
+    pub const fn to_range(&self) -> Range<u32> {
+        self.span.to_range()
+    }
+
+    /// Whether the byte at `offset` lies within the span. See
+    /// [`SourceSpan::contains`].
+    #[must_use]
+    pub const fn contains(&self, offset: u32) -> bool {
+        self.span.contains(offset)
+    }
+
+    /// Whether `span` lies entirely within this label's span. See
+    /// [`SourceSpan::contains_span`].
+    #[must_use]
+    pub const fn contains_span(&self, span: SourceSpan) -> bool {
+        self.span.contains_span(span)
+    }
+
+    /// Widens the label to also cover `span`. See [`SourceSpan::join`].
+    ///
+    /// # Examples
+    /// ```
+    /// use miette::LabeledSpan;
+    ///
+    /// let label = LabeledSpan::at(4..6, "call").join((10, 2).into());
+    /// assert_eq!(label, LabeledSpan::at(4..12, "call"));
+    /// ```
+    #[must_use]
+    pub const fn join(mut self, span: SourceSpan) -> Self {
+        self.span = self.span.join(span);
+        self
+    }
+
+    /// Narrows the label to the part it shares with `span`, or `None` if
+    /// there is a gap between them. See [`SourceSpan::intersect`].
+    #[must_use]
+    pub fn intersect(mut self, span: SourceSpan) -> Option<Self> {
+        self.span = self.span.intersect(span)?;
+        Some(self)
+    }
+
+    /// Moves the label by `delta` bytes, or `None` if it would leave the
+    /// `u32` offset range. See [`SourceSpan::shift`].
+    ///
+    /// # Examples
+    /// ```
+    /// use miette::LabeledSpan;
+    ///
+    /// let label = LabeledSpan::at(4..6, "here");
+    /// assert_eq!(label.clone().shift(2), Some(LabeledSpan::at(6..8, "here")));
+    /// assert_eq!(label.shift(-5), None);
+    /// ```
+    #[must_use]
+    pub fn shift(mut self, delta: i64) -> Option<Self> {
+        self.span = self.span.shift(delta)?;
+        Some(self)
+    }
+
+    /// Makes the label relative to `base`, or `None` if it starts before
+    /// `base`. See [`SourceSpan::rebase`].
+    #[must_use]
+    pub fn rebase(mut self, base: u32) -> Option<Self> {
+        self.span = self.span.rebase(base)?;
+        Some(self)
+    }
+
+    /// Points this label into `source_code` instead of the diagnostic's own
+    /// [`Diagnostic::source_code`], for diagnostics that span several files.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use miette::{LabeledSpan, NamedSource, SourceCode};
+    ///
+    /// let types = Arc::new(NamedSource::new("types.d.ts", "type Id = string;"));
+    /// let label = LabeledSpan::at(5..7, "declared here").with_source_code(types);
+    /// assert_eq!(label.source_code().and_then(SourceCode::name), Some("types.d.ts"));
+    /// ```
+    #[must_use]
+    pub fn with_source_code(mut self, source_code: Arc<dyn SourceCode>) -> Self {
+        self.source_code = Some(source_code);
+        self
+    }
+
+    /// The source this label points into, if it differs from the
+    /// diagnostic's [`Diagnostic::source_code`].
+    #[must_use]
+    pub fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.source_code.as_deref()
+    }
+
     /// Makes a new label at specified span
     ///
     /// # Examples
@@ -183,12 +511,219 @@ impl LabeledSpan {
     /// True if this `LabeledSpan` is a primary span.
     #[must_use]
     pub const fn primary(&self) -> bool {
-        self.primary
+        matches!(self.kind, LabelKind::Primary)
+    }
+
+    /// The role of this label.
+    #[must_use]
+    pub const fn kind(&self) -> LabelKind {
+        self.kind
+    }
+
+    /// The style overriding the theme's style for this label's kind, if any.
+    #[must_use]
+    pub const fn style(&self) -> Option<Style> {
+        self.style
+    }
+}
+
+/// Storage for labels computed from a diagnostic's other fields.
+///
+/// [`Diagnostic::labels`] returns a borrowed slice, so labels built from
+/// spans and format strings at report time need somewhere to live.
+/// `#[derive(Diagnostic)]` requires a `LabelStorage` field whenever a field
+/// is marked `#[label]`, and fills it on the first call to `labels()`.
+///
+/// The labels are built once; change the spans they come from before the
+/// diagnostic is first rendered. Clones start out empty and build their own.
+///
+/// # Examples
+/// ```
+/// use std::fmt;
+///
+/// use miette::{Diagnostic, LabelStorage, LabeledSpan, SourceSpan};
+///
+/// #[derive(Debug)]
+/// struct Unexpected {
+///     span: SourceSpan,
+///     labels: LabelStorage,
+/// }
+///
+/// impl fmt::Display for Unexpected {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("unexpected token")
+///     }
+/// }
+///
+/// impl std::error::Error for Unexpected {}
+///
+/// impl Diagnostic for Unexpected {
+///     fn labels(&self) -> &[LabeledSpan] {
+///         self.labels.get_or_init(|| vec![LabeledSpan::underline(self.span)])
+///     }
+/// }
+///
+/// let diagnostic = Unexpected { span: (4, 1).into(), labels: LabelStorage::new() };
+/// assert_eq!(diagnostic.labels(), [LabeledSpan::new(None, 4, 1)]);
+/// ```
+#[derive(Default)]
+pub struct LabelStorage {
+    labels: OnceLock<Box<[LabeledSpan]>>,
+}
+
+impl LabelStorage {
+    /// Makes a new, empty storage.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { labels: OnceLock::new() }
+    }
+
+    /// Returns the stored labels, building them with `init` on first use.
+    pub fn get_or_init(&self, init: impl FnOnce() -> Vec<LabeledSpan>) -> &[LabeledSpan] {
+        self.labels.get_or_init(|| init().into_boxed_slice())
+    }
+}
+
+impl Clone for LabelStorage {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for LabelStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LabelStorage")
+    }
+}
+
+/// The stored labels are derived state, so every storage compares equal and
+/// does not affect the equality of the diagnostic holding it.
+impl PartialEq for LabelStorage {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for LabelStorage {}
+
+/// A suggested edit: replace the text covered by a [`SourceSpan`] with new
+/// text. An empty span inserts; an empty replacement deletes.
+///
+/// # Examples
+/// ```
+/// use miette::{Applicability, Suggestion};
+///
+/// let source = "var x = 1;";
+/// let suggestion = Suggestion::new(0..3, "let")
+///     .with_message("use `let` instead")
+///     .with_applicability(Applicability::MachineApplicable);
+/// assert_eq!(suggestion.apply(source).as_deref(), Some("let x = 1;"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    message: Option<String>,
+    span: SourceSpan,
+    replacement: String,
+    applicability: Applicability,
+}
+
+impl Suggestion {
+    /// Makes a new suggestion replacing `span` with `replacement`.
+    #[must_use]
+    pub fn new(span: impl Into<SourceSpan>, replacement: impl Into<String>) -> Self {
+        Self {
+            message: None,
+            span: span.into(),
+            replacement: replacement.into(),
+            applicability: Applicability::default(),
+        }
+    }
+
+    /// Sets the message describing this suggestion.
+    #[must_use]
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Sets how confident the suggestion is.
+    #[must_use]
+    pub fn with_applicability(mut self, applicability: Applicability) -> Self {
+        self.applicability = applicability;
+        self
+    }
+
+    /// Gets the (optional) message for this `Suggestion`.
+    #[must_use]
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Returns a reference to the replaced [`SourceSpan`].
+    #[must_use]
+    pub const fn inner(&self) -> &SourceSpan {
+        &self.span
+    }
+
+    /// The text that replaces the span.
+    #[must_use]
+    pub fn replacement(&self) -> &str {
+        &self.replacement
+    }
+
+    /// How confident the suggestion is.
+    #[must_use]
+    pub const fn applicability(&self) -> Applicability {
+        self.applicability
+    }
+
+    /// Applies this suggestion to `source`, returning `None` when the span is
+    /// out of bounds or does not fall on `char` boundaries.
+    #[must_use]
+    pub fn apply(&self, source: &str) -> Option<String> {
+        let start = self.span.offset() as usize;
+        let end = start.checked_add(self.span.len() as usize)?;
+        let (before, after) = (source.get(..start)?, source.get(end..)?);
+        let mut result = String::with_capacity(before.len() + self.replacement.len() + after.len());
+        result.push_str(before);
+        result.push_str(&self.replacement);
+        result.push_str(after);
+        Some(result)
+    }
+}
+
+/// How confident a [`Suggestion`] is. Mirrors rustc's applicability levels.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Default)]
+pub enum Applicability {
+    /// The suggestion is definitely what the user intended and can be
+    /// applied automatically.
+    MachineApplicable,
+    /// The suggestion may be what the user intended, but it is uncertain.
+    MaybeIncorrect,
+    /// The suggestion contains placeholders like `(...)` that the user must
+    /// fill in.
+    HasPlaceholders,
+    /// The applicability of the suggestion is unknown.
+    #[default]
+    Unspecified,
+}
+
+impl Applicability {
+    /// The kebab-case name used by machine-readable renderers.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::MachineApplicable => "machine-applicable",
+            Self::MaybeIncorrect => "maybe-incorrect",
+            Self::HasPlaceholders => "has-placeholders",
+            Self::Unspecified => "unspecified",
+        }
     }
 }
 
 /// Span within a [`SourceCode`]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SourceSpan {
     /// The start of the span.
     offset: u32,
@@ -218,6 +753,129 @@ impl SourceSpan {
     pub const fn is_empty(&self) -> bool {
         self.length == 0
     }
+
+    /// The offset one past the last byte of the span.
+    ///
+    /// Offsets cannot go past [`u32::MAX`], so a span whose `offset + len`
+    /// overflows ends at `u32::MAX`. The other operations measure spans by
+    /// this end.
+    ///
+    /// # Examples
+    /// ```
+    /// use miette::SourceSpan;
+    ///
+    /// assert_eq!(SourceSpan::from((3, 4)).end(), 7);
+    /// assert_eq!(SourceSpan::from((u32::MAX - 1, 5)).end(), u32::MAX);
+    /// ```
+    #[must_use]
+    pub const fn end(self) -> u32 {
+        self.offset.saturating_add(self.length)
+    }
+
+    /// The span as a byte range, `offset..end()`.
+    #[must_use]
+    pub const fn to_range(self) -> Range<u32> {
+        self.offset..self.end()
+    }
+
+    /// The smallest span covering both `self` and `other`, including any gap
+    /// between them.
+    ///
+    /// # Examples
+    /// ```
+    /// use miette::SourceSpan;
+    ///
+    /// let joined = SourceSpan::from(2..4).join(SourceSpan::from(8..9));
+    /// assert_eq!(joined, SourceSpan::from(2..9));
+    /// ```
+    #[must_use]
+    pub const fn join(self, other: Self) -> Self {
+        let start = if self.offset < other.offset { self.offset } else { other.offset };
+        let end = if self.end() > other.end() { self.end() } else { other.end() };
+        Self { offset: start, length: end - start }
+    }
+
+    /// The part of the source covered by both spans.
+    ///
+    /// Spans that only touch intersect in an empty span at the point where
+    /// they meet. Returns `None` if there is a gap between them.
+    ///
+    /// # Examples
+    /// ```
+    /// use miette::SourceSpan;
+    ///
+    /// let a = SourceSpan::from(2..6);
+    /// assert_eq!(a.intersect(SourceSpan::from(4..9)), Some(SourceSpan::from(4..6)));
+    /// assert_eq!(a.intersect(SourceSpan::from(6..9)), Some(SourceSpan::from((6, 0))));
+    /// assert_eq!(a.intersect(SourceSpan::from(7..9)), None);
+    /// ```
+    #[must_use]
+    pub const fn intersect(self, other: Self) -> Option<Self> {
+        let start = if self.offset > other.offset { self.offset } else { other.offset };
+        let end = if self.end() < other.end() { self.end() } else { other.end() };
+        if start > end {
+            return None;
+        }
+        Some(Self { offset: start, length: end - start })
+    }
+
+    /// Whether the byte at `offset` lies within the span. Empty spans
+    /// contain no bytes.
+    #[must_use]
+    pub const fn contains(self, offset: u32) -> bool {
+        self.offset <= offset && offset < self.end()
+    }
+
+    /// Whether `other` lies entirely within the span. An empty `other` may
+    /// sit at either edge.
+    #[must_use]
+    pub const fn contains_span(self, other: Self) -> bool {
+        self.offset <= other.offset && other.end() <= self.end()
+    }
+
+    /// Moves the span by `delta` bytes, keeping its length.
+    ///
+    /// Returns `None` if the moved span would start before `0` or end past
+    /// [`u32::MAX`].
+    ///
+    /// # Examples
+    /// ```
+    /// use miette::SourceSpan;
+    ///
+    /// let span = SourceSpan::from(4..6);
+    /// assert_eq!(span.shift(10), Some(SourceSpan::from(14..16)));
+    /// assert_eq!(span.shift(-4), Some(SourceSpan::from(0..2)));
+    /// assert_eq!(span.shift(-5), None);
+    /// assert_eq!(span.shift(i64::from(u32::MAX - 5)), None);
+    /// ```
+    #[must_use]
+    pub fn shift(self, delta: i64) -> Option<Self> {
+        let offset = u32::try_from(i64::from(self.offset).checked_add(delta)?).ok()?;
+        offset.checked_add(self.length)?;
+        Some(Self { offset, length: self.length })
+    }
+
+    /// Makes the span relative to `base`, as when cutting a fragment that
+    /// starts at `base` out of a larger source.
+    ///
+    /// Returns `None` if the span starts before `base`. The inverse is
+    /// [`shift`](Self::shift) by `base`.
+    ///
+    /// # Examples
+    /// ```
+    /// use miette::SourceSpan;
+    ///
+    /// let span = SourceSpan::from(14..16);
+    /// assert_eq!(span.rebase(10), Some(SourceSpan::from(4..6)));
+    /// assert_eq!(span.rebase(15), None);
+    /// ```
+    #[must_use]
+    pub const fn rebase(self, base: u32) -> Option<Self> {
+        match self.offset.checked_sub(base) {
+            Some(offset) => Some(Self { offset, length: self.length }),
+            None => None,
+        }
+    }
 }
 
 impl From<(u32, u32)> for SourceSpan {
@@ -234,3 +892,19 @@ impl From<Range<u32>> for SourceSpan {
         Self { offset: range.start, length }
     }
 }
+
+impl TryFrom<Range<usize>> for SourceSpan {
+    type Error = TryFromIntError;
+
+    /// Fails if either end of the range does not fit in a `u32`. Reversed
+    /// ranges make empty spans, as with `Range<u32>`.
+    fn try_from(range: Range<usize>) -> Result<Self, Self::Error> {
+        Ok(Self::from(u32::try_from(range.start)?..u32::try_from(range.end)?))
+    }
+}
+
+impl From<SourceSpan> for Range<usize> {
+    fn from(span: SourceSpan) -> Self {
+        span.offset as usize..span.end() as usize
+    }
+}
@@ -0,0 +1,61 @@
+//! Walking a diagnostic's cause chain.
+
+use std::{error::Error, fmt};
+
+use crate::Diagnostic;
+
+/// One link of a cause chain. A [`Diagnostic::diagnostic_source`] keeps its
+/// diagnostic metadata; anything reached through [`Error::source`] is a plain
+/// error.
+#[derive(Clone, Copy)]
+pub(super) enum Cause<'a> {
+    Diagnostic(&'a dyn Diagnostic),
+    Error(&'a (dyn Error + 'static)),
+}
+
+impl<'a> Cause<'a> {
+    fn of_diagnostic(diagnostic: &'a dyn Diagnostic) -> Option<Self> {
+        diagnostic
+            .diagnostic_source()
+            .map(Cause::Diagnostic)
+            .or_else(|| diagnostic.source().map(Cause::Error))
+    }
+
+    fn next(self) -> Option<Self> {
+        match self {
+            Cause::Diagnostic(diagnostic) => Self::of_diagnostic(diagnostic),
+            Cause::Error(error) => error.source().map(Cause::Error),
+        }
+    }
+}
+
+impl fmt::Display for Cause<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cause::Diagnostic(diagnostic) => fmt::Display::fmt(diagnostic, f),
+            Cause::Error(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+/// Iterator over the causes of a diagnostic, nearest first. The diagnostic
+/// itself is not included.
+pub(super) struct Causes<'a> {
+    next: Option<Cause<'a>>,
+}
+
+impl<'a> Causes<'a> {
+    pub(super) fn new(diagnostic: &'a dyn Diagnostic) -> Self {
+        Self { next: Cause::of_diagnostic(diagnostic) }
+    }
+}
+
+impl<'a> Iterator for Causes<'a> {
+    type Item = Cause<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cause = self.next?;
+        self.next = cause.next();
+        Some(cause)
+    }
+}
@@ -0,0 +1,192 @@
+use std::{borrow::Cow, fmt};
+
+use rustc_hash::FxHashMap;
+
+use super::{
+    location::{Location, Locator, flatten},
+    xml::escape,
+};
+use crate::{
+    ColumnUnit, LabeledSpan, Severity, SourceCode, protocol::Diagnostic, renderers::ReportHandler,
+    source::remap_labels,
+};
+
+/**
+Renders batches of diagnostics as [Checkstyle] XML, which Jenkins' Warnings
+NG plugin and many editor plugins read.
+
+Diagnostics, and their related diagnostics, are grouped into a `<file>` per
+[`SourceCode::name`], in the order the files first come up. Each becomes an
+`<error>` with:
+
+- the 1-based `line` and `column` of the primary label, or of the first
+  label if none is primary, counted like
+  [`JSONReportHandler`](crate::JSONReportHandler) counts them;
+- the `severity`: `error`, `warning`, or `info` for [`Severity::Advice`];
+- the `message`, and [`Diagnostic::code`] as the `source`.
+
+Diagnostics without a named source are listed under a `<file>` with an
+empty name.
+
+[Checkstyle]: https://checkstyle.org
+
+# Examples
+```
+use std::sync::Arc;
+
+use miette::{CheckstyleReportHandler, LabeledSpan, MietteDiagnostic, NamedSource};
+
+let source = Arc::new(NamedSource::new("src/main.js", String::from("var x = 1;\n")));
+let diagnostic = MietteDiagnostic::new("unexpected `var`")
+    .with_code("no-var")
+    .with_label(LabeledSpan::at(0..3, "declared here"))
+    .with_source_code(source);
+
+let mut output = String::new();
+CheckstyleReportHandler::new().render_reports(&mut output, &[&diagnostic]).unwrap();
+assert!(output.contains(
+    r#"<error line="1" column="1" severity="error" message="unexpected `var`" source="no-var"/>"#
+));
+```
+*/
+#[derive(Debug, Clone)]
+pub struct CheckstyleReportHandler;
+
+impl CheckstyleReportHandler {
+    /// Create a new [`CheckstyleReportHandler`]. There are no customization
+    /// options.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for CheckstyleReportHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CheckstyleReportHandler {
+    /// Render a document with a single [`Diagnostic`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered document fails.
+    pub fn render_report(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        self.render_reports(f, &[diagnostic])
+    }
+
+    /// Render a document with every diagnostic of the batch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered document fails.
+    #[expect(clippy::unused_self, reason = "keeps a consistent renderer API")]
+    pub fn render_reports(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> fmt::Result {
+        let mut flattened = Vec::new();
+        for &diagnostic in diagnostics {
+            flatten(&mut flattened, diagnostic, None);
+        }
+        let mut locator = Locator::default();
+        let mut files = Vec::<(Cow<'_, str>, Vec<Error<'_>>)>::new();
+        let mut file_indices = FxHashMap::<Cow<'_, str>, usize>::default();
+        for (diagnostic, source) in flattened {
+            // Remapped labels only live until the end of this match, so they
+            // cannot share the batch's scanners, and their file name is copied.
+            let (name, location) = match remap_labels(diagnostic.labels(), source) {
+                Cow::Borrowed(labels) => locate(&mut locator, labels, source),
+                Cow::Owned(labels) => {
+                    let (name, location) = locate(&mut Locator::default(), &labels, source);
+                    (Cow::Owned(name.into_owned()), location)
+                }
+            };
+            let index = *file_indices.entry(name).or_insert_with_key(|name| {
+                files.push((name.clone(), Vec::new()));
+                files.len() - 1
+            });
+            files[index].1.push(Error { diagnostic, location });
+        }
+
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(f, r#"<checkstyle version="4.3">"#)?;
+        for (name, errors) in &files {
+            writeln!(f, r#"<file name="{}">"#, escape(name))?;
+            for error in errors {
+                error.render(f)?;
+            }
+            writeln!(f, "</file>")?;
+        }
+        writeln!(f, "</checkstyle>")
+    }
+}
+
+/// A diagnostic and where its primary label starts.
+struct Error<'a> {
+    diagnostic: &'a dyn Diagnostic,
+    location: Option<Location>,
+}
+
+impl Error<'_> {
+    fn render(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "<error")?;
+        if let Some(Location { start, .. }) = self.location {
+            write!(f, r#" line="{}" column="{}""#, start.line + 1, start.column + 1)?;
+        }
+        let severity = match self.diagnostic.severity() {
+            Some(Severity::Error) | None => "error",
+            Some(Severity::Warning) => "warning",
+            Some(Severity::Advice) => "info",
+        };
+        write!(f, r#" severity="{severity}""#)?;
+        write!(f, r#" message="{}""#, escape(&self.diagnostic.to_string()))?;
+        if let Some(code) = self.diagnostic.code() {
+            write!(f, r#" source="{}""#, escape(&code))?;
+        }
+        writeln!(f, "/>")
+    }
+}
+
+/// The file name and location of the primary label, or of the first label
+/// if none is primary. Diagnostics without one are in their source's file.
+fn locate<'a>(
+    locator: &mut Locator<'a>,
+    labels: &'a [LabeledSpan],
+    source: Option<&'a dyn SourceCode>,
+) -> (Cow<'a, str>, Option<Location>) {
+    let label = labels.iter().find(|label| label.primary()).or_else(|| labels.first());
+    let source = label.and_then(LabeledSpan::source_code).or(source);
+    let name = Cow::Borrowed(source.and_then(SourceCode::name).unwrap_or_default());
+    let location = label
+        .zip(source)
+        .and_then(|(label, source)| locator.locate(source, *label.inner(), ColumnUnit::Byte));
+    (name, location)
+}
+
+/// Batches are rendered as one document, and a single report as a document
+/// of its own.
+impl ReportHandler for CheckstyleReportHandler {
+    fn render_report(
+        &self,
+        mut f: &mut dyn fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        Self::render_report(self, &mut f, diagnostic)
+    }
+
+    fn render_reports(
+        &self,
+        mut f: &mut dyn fmt::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> fmt::Result {
+        Self::render_reports(self, &mut f, diagnostics)
+    }
+}
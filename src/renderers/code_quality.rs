@@ -0,0 +1,302 @@
+use std::{borrow::Cow, fmt};
+
+use rustc_hash::FxHashMap;
+
+use super::{
+    json::escape,
+    location::{Location, Locator, flatten},
+};
+use crate::{
+    ColumnUnit, LabeledSpan, Severity, SourceCode, SourceSpan, protocol::Diagnostic,
+    renderers::ReportHandler, source::remap_labels,
+};
+
+/// The five severities of a GitLab Code Quality issue.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum CodeQualitySeverity {
+    /// `info`
+    Info,
+    /// `minor`
+    Minor,
+    /// `major`
+    Major,
+    /// `critical`
+    Critical,
+    /// `blocker`
+    Blocker,
+}
+
+impl CodeQualitySeverity {
+    /// The severity as written in a report.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Minor => "minor",
+            Self::Major => "major",
+            Self::Critical => "critical",
+            Self::Blocker => "blocker",
+        }
+    }
+}
+
+/**
+Renders batches of diagnostics as a GitLab [Code Quality] report, the Code
+Climate issue format merge request widgets compare between pipelines.
+
+Each diagnostic, and each of its related diagnostics, becomes an issue:
+
+- The message is the `description`, and [`Diagnostic::code`] the
+  `check_name`, or `miette` for diagnostics without a code.
+- [`Diagnostic::severity`] is mapped onto the five Code Quality severities,
+  by default [`Severity::Advice`] to `info`, [`Severity::Warning`] to
+  `minor` and [`Severity::Error`] to `major`. See
+  [`CodeQualityReportHandler::with_severity`].
+- The primary label, or the first label if none is primary, gives the
+  `location`: the `path` ([`SourceCode::name`]), `lines` and 1-based
+  `positions`, with columns counting UTF-16 code units. Issues without one
+  are placed on line 1 of their source's file.
+- Diagnostics that are not in a named file are left out, as every issue
+  needs a `path`.
+- The `fingerprint` is a hash of the code, the path and the text of the
+  lines the label is on, so it stays the same when unrelated lines move.
+  Issues that would share a fingerprint are told apart by how many came
+  before them in the batch.
+
+[Code Quality]: https://docs.gitlab.com/ci/testing/code_quality/#code-quality-report-format
+
+# Examples
+```
+use std::sync::Arc;
+
+use miette::{CodeQualityReportHandler, LabeledSpan, MietteDiagnostic, NamedSource};
+
+let source = Arc::new(NamedSource::new("src/main.js", String::from("var x = 1;\n")));
+let diagnostic = MietteDiagnostic::new("unexpected `var`")
+    .with_code("no-var")
+    .with_label(LabeledSpan::at(0..3, "declared here"))
+    .with_source_code(source);
+
+let mut report = String::new();
+CodeQualityReportHandler::new().render_reports(&mut report, &[&diagnostic]).unwrap();
+assert!(report.contains(r#""check_name": "no-var""#));
+assert!(report.contains(r#""lines": {"begin": 1,"end": 1}"#));
+```
+*/
+#[derive(Debug, Clone)]
+pub struct CodeQualityReportHandler {
+    error: CodeQualitySeverity,
+    warning: CodeQualitySeverity,
+    advice: CodeQualitySeverity,
+}
+
+impl CodeQualityReportHandler {
+    /// Create a new [`CodeQualityReportHandler`] with the default severities.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            error: CodeQualitySeverity::Major,
+            warning: CodeQualitySeverity::Minor,
+            advice: CodeQualitySeverity::Info,
+        }
+    }
+
+    /// Report diagnostics of `severity` as `level`, such as errors as
+    /// [`CodeQualitySeverity::Critical`].
+    #[must_use]
+    pub const fn with_severity(mut self, severity: Severity, level: CodeQualitySeverity) -> Self {
+        match severity {
+            Severity::Error => self.error = level,
+            Severity::Warning => self.warning = level,
+            Severity::Advice => self.advice = level,
+        }
+        self
+    }
+
+    /// Render a report with the issues of a single [`Diagnostic`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered report fails.
+    pub fn render_report(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        self.render_reports(f, &[diagnostic])
+    }
+
+    /// Render a report with the issues of every diagnostic of the batch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered report fails.
+    pub fn render_reports(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> fmt::Result {
+        let mut issues = Vec::new();
+        for &diagnostic in diagnostics {
+            flatten(&mut issues, diagnostic, None);
+        }
+        let mut locator = Locator::default();
+        let mut occurrences = FxHashMap::<u128, u64>::default();
+        let mut add_comma = false;
+        write!(f, "[")?;
+        for (diagnostic, source) in issues {
+            // Remapped labels only live until the end of this match, so they
+            // cannot share the batch's scanners, and their file name is copied.
+            let issue = match remap_labels(diagnostic.labels(), source) {
+                Cow::Borrowed(labels) => Issue::new(&mut locator, diagnostic, labels, source),
+                Cow::Owned(labels) => {
+                    let issue = Issue::new(&mut Locator::default(), diagnostic, &labels, source);
+                    Issue { path: issue.path.map(|path| Cow::Owned(path.into_owned())), ..issue }
+                }
+            };
+            let Some(path) = &issue.path else { continue };
+            if add_comma {
+                write!(f, ",")?;
+            } else {
+                add_comma = true;
+            }
+            let occurrence = occurrences.entry(issue.hasher.finish()).or_default();
+            let mut hasher = issue.hasher.clone();
+            hasher.write(&occurrence.to_le_bytes());
+            *occurrence += 1;
+            self.render_issue(f, diagnostic, path, issue.location, hasher.finish())?;
+        }
+        write!(f, "]")
+    }
+
+    fn render_issue(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+        path: &str,
+        location: Option<Location>,
+        fingerprint: u128,
+    ) -> fmt::Result {
+        let severity = match diagnostic.severity().unwrap_or_default() {
+            Severity::Error => self.error,
+            Severity::Warning => self.warning,
+            Severity::Advice => self.advice,
+        };
+        write!(f, r#"{{"type": "issue","#)?;
+        write!(f, r#""description": "{}","#, escape(&diagnostic.to_string()))?;
+        let code = diagnostic.code();
+        write!(f, r#""check_name": "{}","#, escape(code.as_deref().unwrap_or("miette")))?;
+        write!(f, r#""fingerprint": "{fingerprint:032x}","#)?;
+        write!(f, r#""severity": "{}","#, severity.as_str())?;
+        write!(f, r#""location": {{"path": "{}","#, escape(path))?;
+        if let Some(Location { start, end }) = location {
+            write!(f, r#""lines": {{"begin": {},"end": {}}},"#, start.line + 1, end.line + 1)?;
+            write!(f, r#""positions": {{"#)?;
+            write!(
+                f,
+                r#""begin": {{"line": {},"column": {}}},"#,
+                start.line + 1,
+                start.column + 1
+            )?;
+            write!(f, r#""end": {{"line": {},"column": {}}}}}"#, end.line + 1, end.column + 1)?;
+        } else {
+            write!(f, r#""lines": {{"begin": 1}}"#)?;
+        }
+        write!(f, "}}}}")
+    }
+}
+
+impl Default for CodeQualityReportHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where an issue is, and its fingerprint before telling apart issues that
+/// would share it.
+struct Issue<'a> {
+    path: Option<Cow<'a, str>>,
+    location: Option<Location>,
+    hasher: Fnv,
+}
+
+impl<'a> Issue<'a> {
+    fn new(
+        locator: &mut Locator<'a>,
+        diagnostic: &dyn Diagnostic,
+        labels: &'a [LabeledSpan],
+        source: Option<&'a dyn SourceCode>,
+    ) -> Self {
+        let label = labels.iter().find(|label| label.primary()).or_else(|| labels.first());
+        let source = label.and_then(LabeledSpan::source_code).or(source);
+        let path =
+            source.and_then(SourceCode::name).filter(|name| !name.is_empty()).map(Cow::Borrowed);
+        let mut location = None;
+        let mut lines: &[u8] = &[];
+        if let (Some(label), Some(source)) = (label, source) {
+            location = locator.locate(source, *label.inner(), ColumnUnit::Utf16);
+            if location.is_some() {
+                lines = surrounding_lines(source.data(), *label.inner());
+            }
+        }
+
+        let mut hasher = Fnv::new();
+        hasher.write(diagnostic.code().as_deref().unwrap_or_default().as_bytes());
+        hasher.write(path.as_deref().unwrap_or_default().as_bytes());
+        hasher.write(lines.trim_ascii());
+        Self { path, location, hasher }
+    }
+}
+
+/// The full lines of `data` that `span` is on, without their line breaks.
+/// `span` must be in bounds.
+fn surrounding_lines(data: &[u8], span: SourceSpan) -> &[u8] {
+    let is_break = |byte: &u8| matches!(byte, b'\n' | b'\r');
+    let (offset, end) = (span.offset() as usize, span.offset() as usize + span.len() as usize);
+    let start = data[..offset].iter().rposition(is_break).map_or(0, |i| i + 1);
+    let end = data[end..].iter().position(is_break).map_or(data.len(), |i| end + i);
+    &data[start..end]
+}
+
+/// 128-bit FNV-1a, a hash that stays the same across platforms and
+/// releases, unlike [`std::hash::Hasher`]s.
+#[derive(Clone)]
+struct Fnv(u128);
+
+impl Fnv {
+    const fn new() -> Self {
+        Self(0x6c62_272e_07bb_0142_62b8_2175_6295_c58d)
+    }
+
+    /// Hashes a length-prefixed field, so fields cannot run into each other.
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in (bytes.len() as u64).to_le_bytes().iter().chain(bytes) {
+            self.0 ^= u128::from(byte);
+            self.0 = self.0.wrapping_mul(0x0000_0000_0100_0000_0000_0000_0000_013b);
+        }
+    }
+
+    const fn finish(&self) -> u128 {
+        self.0
+    }
+}
+
+/// Batches are rendered as one array of issues, and a single report as an
+/// array of its own.
+impl ReportHandler for CodeQualityReportHandler {
+    fn render_report(
+        &self,
+        mut f: &mut dyn fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        Self::render_report(self, &mut f, diagnostic)
+    }
+
+    fn render_reports(
+        &self,
+        mut f: &mut dyn fmt::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> fmt::Result {
+        Self::render_reports(self, &mut f, diagnostics)
+    }
+}
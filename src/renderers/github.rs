@@ -0,0 +1,244 @@
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+};
+
+use super::location::{Location, Locator, flatten};
+use crate::{
+    ColumnUnit, LabeledSpan, Severity, SourceCode, protocol::Diagnostic, renderers::ReportHandler,
+    source::remap_labels,
+};
+
+/**
+Renders diagnostics as GitHub Actions [workflow commands], which show up as
+annotations on the lines of a pull request.
+
+Each diagnostic, and each of its related diagnostics, becomes one command:
+
+```text
+::error file=src/main.js,line=2,col=1,endLine=2,endColumn=4,title=no-var::unexpected `var`
+```
+
+- [`Diagnostic::severity`] picks the command: `error`, `warning`, or
+  `notice` for [`Severity::Advice`].
+- The primary label, or the first label if none is primary, gives the
+  `file` ([`SourceCode::name`]) and the 1-based `line`, `col`, `endLine` and
+  `endColumn`. Columns count UTF-16 code units, and `endColumn` is the
+  column just past the label.
+- [`Diagnostic::code`] is the `title`.
+- [`Diagnostic::help`] and [`Diagnostic::note`] follow the message on lines
+  of their own.
+
+# Examples
+```
+use std::sync::Arc;
+
+use miette::{GitHubActionsReportHandler, LabeledSpan, MietteDiagnostic, NamedSource};
+
+let source = Arc::new(NamedSource::new("src/main.js", String::from("var x = 1;\n")));
+let diagnostic = MietteDiagnostic::new("unexpected `var`")
+    .with_code("no-var")
+    .with_help("use `let` instead")
+    .with_label(LabeledSpan::at(0..3, "declared here"))
+    .with_source_code(source);
+
+let mut output = String::new();
+GitHubActionsReportHandler::new().render_report(&mut output, &diagnostic).unwrap();
+assert_eq!(
+    output,
+    "::error file=src/main.js,line=1,col=1,endLine=1,endColumn=4,title=no-var\
+     ::unexpected `var`%0A%0Ahelp: use `let` instead\n",
+);
+```
+
+[workflow commands]: https://docs.github.com/en/actions/reference/workflows-and-actions/workflow-commands
+*/
+#[derive(Debug, Clone)]
+pub struct GitHubActionsReportHandler;
+
+impl GitHubActionsReportHandler {
+    /// Create a new [`GitHubActionsReportHandler`]. There are no
+    /// customization options.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for GitHubActionsReportHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GitHubActionsReportHandler {
+    /// Render a [`Diagnostic`] and its related diagnostics, one command per
+    /// line.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered commands fails.
+    pub fn render_report(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        self.render_reports(f, &[diagnostic])
+    }
+
+    /// Render a batch of diagnostics, looking up the lines of each source
+    /// once for the whole batch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered commands fails.
+    #[expect(clippy::unused_self, reason = "keeps a consistent renderer API")]
+    pub fn render_reports(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> fmt::Result {
+        let mut commands = Vec::new();
+        for &diagnostic in diagnostics {
+            flatten(&mut commands, diagnostic, None);
+        }
+        let mut locator = Locator::default();
+        for (diagnostic, source) in commands {
+            Self::render_command(f, &mut locator, diagnostic, source)?;
+        }
+        Ok(())
+    }
+
+    /// Renders one command. `source` is the diagnostic's source code, or that
+    /// of the diagnostic it is related to.
+    fn render_command<'a>(
+        f: &mut impl fmt::Write,
+        locator: &mut Locator<'a>,
+        diagnostic: &'a dyn Diagnostic,
+        source: Option<&'a dyn SourceCode>,
+    ) -> fmt::Result {
+        let command = match diagnostic.severity() {
+            Some(Severity::Error) | None => "error",
+            Some(Severity::Warning) => "warning",
+            Some(Severity::Advice) => "notice",
+        };
+        write!(f, "::{command}")?;
+        let mut properties = Properties { f: &mut *f, count: 0 };
+        // Remapped labels only live until the end of this command, so they
+        // cannot share the batch's scanners.
+        match remap_labels(diagnostic.labels(), source) {
+            Cow::Borrowed(labels) => properties.location(locator, labels, source)?,
+            Cow::Owned(labels) => properties.location(&mut Locator::default(), &labels, source)?,
+        }
+        if let Some(code) = diagnostic.code() {
+            properties.write("title", &code)?;
+        }
+
+        write!(f, "::{}", Escape::data(&diagnostic.to_string()))?;
+        if let Some(help) = diagnostic.help() {
+            write!(f, "%0A%0Ahelp: {}", Escape::data(&help))?;
+        }
+        if let Some(note) = diagnostic.note() {
+            write!(f, "%0A%0Anote: {}", Escape::data(&note))?;
+        }
+        writeln!(f)
+    }
+}
+
+/// The comma-separated `key=value` properties of a command.
+struct Properties<'f, W> {
+    f: &'f mut W,
+    count: usize,
+}
+
+impl<W: fmt::Write> Properties<'_, W> {
+    fn write(&mut self, key: &str, value: &dyn fmt::Display) -> fmt::Result {
+        let separator = if self.count == 0 { ' ' } else { ',' };
+        self.count += 1;
+        write!(self.f, "{separator}{key}={}", Escape::property(&value.to_string()))
+    }
+
+    /// Writes the file and position of the primary label, or of the first
+    /// label if none is primary.
+    fn location<'a>(
+        &mut self,
+        locator: &mut Locator<'a>,
+        labels: &'a [LabeledSpan],
+        source: Option<&'a dyn SourceCode>,
+    ) -> fmt::Result {
+        let Some(label) = labels.iter().find(|label| label.primary()).or_else(|| labels.first())
+        else {
+            return Ok(());
+        };
+        let Some(source) = label.source_code().or(source) else { return Ok(()) };
+        let Some(name) = source.name() else { return Ok(()) };
+        self.write("file", &name)?;
+        if let Some(Location { start, end }) =
+            locator.locate(source, *label.inner(), ColumnUnit::Utf16)
+        {
+            self.write("line", &(start.line + 1))?;
+            self.write("col", &(start.column + 1))?;
+            self.write("endLine", &(end.line + 1))?;
+            self.write("endColumn", &(end.column + 1))?;
+        }
+        Ok(())
+    }
+}
+
+/// Escapes text for a workflow command: `%`, `\r` and `\n` everywhere, and
+/// also `:` and `,` in property values.
+struct Escape<'a> {
+    text: &'a str,
+    property: bool,
+}
+
+impl<'a> Escape<'a> {
+    const fn data(text: &'a str) -> Self {
+        Self { text, property: false }
+    }
+
+    const fn property(text: &'a str) -> Self {
+        Self { text, property: true }
+    }
+}
+
+impl fmt::Display for Escape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.text.chars() {
+            match c {
+                '%' => f.write_str("%25")?,
+                '\r' => f.write_str("%0D")?,
+                '\n' => f.write_str("%0A")?,
+                ':' if self.property => f.write_str("%3A")?,
+                ',' if self.property => f.write_str("%2C")?,
+                _ => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Every command ends with a line break, so batches need no separators.
+impl ReportHandler for GitHubActionsReportHandler {
+    fn render_report(
+        &self,
+        mut f: &mut dyn fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        Self::render_report(self, &mut f, diagnostic)
+    }
+
+    fn render_reports(
+        &self,
+        mut f: &mut dyn fmt::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> fmt::Result {
+        Self::render_reports(self, &mut f, diagnostics)
+    }
+}
+
+#[test]
+fn test_escape() {
+    assert_eq!(Escape::data("50%: a,b\r\n").to_string(), "50%25: a,b%0D%0A");
+    assert_eq!(Escape::property("50%: a,b\r\n").to_string(), "50%25%3A a%2Cb%0D%0A");
+}
@@ -144,9 +144,9 @@ impl GraphicalReportHandler {
             let marker = if hl.len() == 0 {
                 chars.uarrow
             } else if hl.has_label() {
-                chars.underbar
+                chars.underbar_for(hl.kind)
             } else {
-                chars.underline
+                chars.underline_for(hl.kind)
             };
             write!(
                 f,
@@ -156,7 +156,7 @@ impl GraphicalReportHandler {
                     left: num_left,
                     marker,
                     right: num_right,
-                    line: chars.underline,
+                    line: chars.underline_for(hl.kind),
                 }
                 .style(hl.style)
             )?;
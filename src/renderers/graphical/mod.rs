@@ -11,10 +11,12 @@
 //! - [`handler`] — the [`GraphicalReportHandler`] type and its builder API.
 //! - [`report`] — the top level: title, help/note, and wrapping.
 //! - [`snippet`] — reads the labelled spans and lays out the source snippets.
+//! - [`suggestion`] — draws suggested edits as `-`/`+` diffs.
 //! - [`gutter`] — the line-number column and multi-line span gutters.
 //! - [`label`] — the underlines and labels drawn under the source text.
 //! - [`mod@line`] — the [`Line`](line::Line) model, line splitting, and width math.
 //! - [`span`] — [`FancySpan`](span::FancySpan), a styled labelled span.
+//! - [`summary`] — the totals line printed after a batch of reports.
 
 mod gutter;
 mod handler;
@@ -23,6 +25,8 @@ mod line;
 mod report;
 mod snippet;
 mod span;
+mod suggestion;
+mod summary;
 mod theme;
 
 pub use handler::GraphicalReportHandler;
@@ -1,18 +1,59 @@
 //! Diagnostic-level rendering: everything except the source snippets.
 //!
-//! [`render_report`](GraphicalReportHandler::render_report) is the entry point.
+//! [`render_report`](GraphicalReportHandler::render_report) and its batch
+//! form [`render_reports`](GraphicalReportHandler::render_reports) are the
+//! entry points.
 //! It renders the title, hands off to
-//! [`render_snippets`](GraphicalReportHandler::render_snippets), then renders
-//! the help/note footer. Each block of prose is wrapped to the terminal width
-//! using the shared [`wrap_options`](GraphicalReportHandler::wrap_options)
-//! helper.
+//! [`render_snippets`](GraphicalReportHandler::render_snippets) and
+//! [`render_suggestions`](GraphicalReportHandler::render_suggestions), then renders
+//! the help/note footer and the `caused by:` chain. Each block of prose is
+//! wrapped to the terminal width using the shared
+//! [`wrap_options`](GraphicalReportHandler::wrap_options) helper. Related
+//! diagnostics are rendered last, as nested reports indented under their
+//! parent.
 
 use std::fmt;
 
-use owo_colors::OwoColorize;
+use owo_colors::{OwoColorize, Style};
 
-use super::handler::{GraphicalReportHandler, LinkStyle};
-use crate::{Diagnostic, Severity};
+use super::{
+    super::causes::{Cause, Causes},
+    handler::{GraphicalReportHandler, LinkStyle},
+    snippet::Scanners,
+};
+use crate::{Diagnostic, ReportHandler, Severity, SourceCode};
+
+/// Indentation of each level of related diagnostics.
+const RELATED_INDENT: &str = "    ";
+
+/// Prefixes every non-empty line written through it with [`RELATED_INDENT`].
+///
+/// Nested related diagnostics wrap the writer again, so each level adds one
+/// more indent. Holding a trait object keeps that recursion from
+/// instantiating a new writer type per level.
+struct Indented<'a> {
+    inner: &'a mut dyn fmt::Write,
+    at_line_start: bool,
+}
+
+impl<'a> Indented<'a> {
+    fn new(inner: &'a mut dyn fmt::Write) -> Self {
+        Self { inner, at_line_start: true }
+    }
+}
+
+impl fmt::Write for Indented<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for line in s.split_inclusive('\n') {
+            if self.at_line_start && line != "\n" {
+                self.inner.write_str(RELATED_INDENT)?;
+            }
+            self.inner.write_str(line)?;
+            self.at_line_start = line.ends_with('\n');
+        }
+        Ok(())
+    }
+}
 
 impl GraphicalReportHandler {
     /// Render a [`Diagnostic`].
@@ -25,20 +66,140 @@ impl GraphicalReportHandler {
         f: &mut impl fmt::Write,
         diagnostic: &dyn Diagnostic,
     ) -> fmt::Result {
+        self.render_diagnostic(f, diagnostic, &mut Scanners::default())
+    }
+
+    /// Render a batch of [`Diagnostic`]s, byte for byte as rendering each in
+    /// turn with [`GraphicalReportHandler::render_report`] would.
+    ///
+    /// Labels in the same source share one line index across the batch, so a
+    /// large file with many diagnostics is scanned once rather than once per
+    /// report.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered reports fails.
+    pub fn render_reports(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> fmt::Result {
+        let mut scanners = Scanners::default();
+        scanners.seed(diagnostics);
+        for &diagnostic in diagnostics {
+            self.render_diagnostic(f, diagnostic, &mut scanners)?;
+        }
+        Ok(())
+    }
+
+    fn render_diagnostic<'a>(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &'a dyn Diagnostic,
+        scanners: &mut Scanners<'a>,
+    ) -> fmt::Result {
+        self.render_with_source(f, diagnostic, diagnostic.source_code(), false, scanners)
+    }
+
+    /// Renders a whole report for `diagnostic`, with its snippets and
+    /// suggestions read from `src`. Top-level reports and related ones
+    /// share it, so they cannot drift apart; related ones are `indented` by
+    /// [`RELATED_INDENT`].
+    fn render_with_source<'a>(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &'a dyn Diagnostic,
+        src: Option<&'a dyn SourceCode>,
+        indented: bool,
+        scanners: &mut Scanners<'a>,
+    ) -> fmt::Result {
+        if indented {
+            return self.render_with_source(
+                &mut Indented::new(f),
+                diagnostic,
+                src,
+                false,
+                scanners,
+            );
+        }
         writeln!(f)?;
         self.render_title(f, diagnostic)?;
-        let src = diagnostic.source_code();
-        self.render_snippets(f, diagnostic, src)?;
+        self.render_snippets(f, diagnostic, src, scanners)?;
+        self.render_suggestions(f, diagnostic, src, scanners)?;
         self.render_footer(f, diagnostic)?;
+        self.render_causes(f, diagnostic, src, scanners)?;
+        self.render_related(f, diagnostic, src, scanners)?;
         Ok(())
     }
 
+    /// Renders each of [`Diagnostic::related`] as a nested report. Related
+    /// diagnostics without their own source code borrow `parent_src`.
+    fn render_related<'a>(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &'a dyn Diagnostic,
+        parent_src: Option<&'a dyn SourceCode>,
+        scanners: &mut Scanners<'a>,
+    ) -> fmt::Result {
+        for related in diagnostic.related() {
+            let src = related.source_code().or(parent_src);
+            self.render_with_source(f, &**related, src, true, scanners)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the [`Error::source`](std::error::Error::source) chain as a
+    /// `caused by:` section, one line per cause. A cause reached through
+    /// [`Diagnostic::diagnostic_source`] also gets its snippets and footer,
+    /// indented under its line.
+    fn render_causes<'a>(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &'a dyn Diagnostic,
+        parent_src: Option<&'a dyn SourceCode>,
+        scanners: &mut Scanners<'a>,
+    ) -> fmt::Result {
+        const PREFIX: &str = "  caused by: ";
+        let mut causes = Causes::new(diagnostic).peekable();
+        if causes.peek().is_none() {
+            return Ok(());
+        }
+        let (severity_style, _) = self.severity_style(diagnostic);
+        let width = self.termwidth.saturating_sub(4);
+        let initial_indent = PREFIX.style(severity_style).to_string();
+        f.write_char('\n')?;
+        for cause in causes {
+            let message = match cause {
+                Cause::Diagnostic(cause) => match cause.code() {
+                    Some(code) => format!("{code}: {cause}"),
+                    None => cause.to_string(),
+                },
+                Cause::Error(cause) => cause.to_string(),
+            };
+            let opts = Self::wrap_options(width, &initial_indent, "             ");
+            Self::write_fill(f, &message, opts)?;
+            f.write_char('\n')?;
+            if let Cause::Diagnostic(cause) = cause {
+                let mut f = Indented::new(f);
+                let src = cause.source_code().or(parent_src);
+                self.render_snippets(&mut f, cause, src, scanners)?;
+                self.render_suggestions(&mut f, cause, src, scanners)?;
+                self.render_footer(&mut f, cause)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn severity_style(&self, diagnostic: &dyn Diagnostic) -> (Style, &'static str) {
+        match diagnostic.severity() {
+            Some(Severity::Error) | None => (self.theme.styles.error, self.theme.characters.error),
+            Some(Severity::Warning) => (self.theme.styles.warning, self.theme.characters.warning),
+            Some(Severity::Advice) => (self.theme.styles.advice, self.theme.characters.advice),
+        }
+    }
+
     fn render_title(&self, f: &mut impl fmt::Write, diagnostic: &dyn Diagnostic) -> fmt::Result {
-        let (severity_style, severity_icon) = match diagnostic.severity() {
-            Some(Severity::Error) | None => (self.theme.styles.error, &self.theme.characters.error),
-            Some(Severity::Warning) => (self.theme.styles.warning, &self.theme.characters.warning),
-            Some(Severity::Advice) => (self.theme.styles.advice, &self.theme.characters.advice),
-        };
+        let (severity_style, severity_icon) = self.severity_style(diagnostic);
 
         let width = self.termwidth.saturating_sub(2);
 
@@ -120,7 +281,7 @@ impl GraphicalReportHandler {
     }
 
     /// Builds the [`textwrap::Options`] shared by every wrapped block.
-    fn wrap_options<'a>(
+    pub(super) fn wrap_options<'a>(
         width: usize,
         initial_indent: &'a str,
         subsequent_indent: &'a str,
@@ -130,7 +291,11 @@ impl GraphicalReportHandler {
             .subsequent_indent(subsequent_indent)
     }
 
-    fn write_fill(f: &mut impl fmt::Write, text: &str, opts: textwrap::Options<'_>) -> fmt::Result {
+    pub(super) fn write_fill(
+        f: &mut impl fmt::Write,
+        text: &str,
+        opts: textwrap::Options<'_>,
+    ) -> fmt::Result {
         if Self::fits_on_line(text, &opts) {
             f.write_str(opts.initial_indent)?;
             f.write_str(text.trim_end_matches(' '))
@@ -219,6 +384,24 @@ impl GraphicalReportHandler {
     }
 }
 
+impl ReportHandler for GraphicalReportHandler {
+    fn render_report(
+        &self,
+        mut f: &mut dyn fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        Self::render_report(self, &mut f, diagnostic)
+    }
+
+    fn render_reports(
+        &self,
+        mut f: &mut dyn fmt::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> fmt::Result {
+        Self::render_reports(self, &mut f, diagnostics)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,16 +1,19 @@
 //! Source-snippet layout.
 //!
-//! [`render_snippets`](GraphicalReportHandler::render_snippets) reads every
-//! label's span in a single forward scan and merges overlapping spans into
-//! contexts.
+//! [`render_snippets`](GraphicalReportHandler::render_snippets) groups the
+//! labels by the source they point into, reads every label's span with a
+//! single forward scan per source, and merges overlapping spans into
+//! contexts. The scans are kept in [`Scanners`], which a batch of reports
+//! shares.
 //! [`render_context`](GraphicalReportHandler::render_context) then draws one
 //! context: the `[file:line:col]` header, each source line (via
 //! [`render_line_text`](GraphicalReportHandler::render_line_text)), and the
 //! gutters/underlines/labels delegated to the sibling modules.
 
-use std::{borrow::Cow, cmp::max, fmt};
+use std::{borrow::Cow, cmp::max, fmt, ptr};
 
 use owo_colors::OwoColorize;
+use rustc_hash::FxHashMap;
 
 use super::{
     handler::GraphicalReportHandler,
@@ -18,32 +21,92 @@ use super::{
     span::{FancySpan, LabelRenderMode},
 };
 use crate::{
-    Diagnostic, LabeledSpan, SourceCode, SourceSpan,
-    source::reader::{SpanContents, SpanScanner},
+    Diagnostic, LabelKind, LabeledSpan, LineColumn, SourceCode, SourceSpan,
+    source::{
+        reader::{SpanContents, SpanScanner},
+        remap_labels,
+    },
 };
 
+/// One forward [`SpanScanner`] per source, shared by every report of a
+/// batch so each source's line index is built once. Sources are identified
+/// by the address and length of their data.
+#[derive(Default)]
+pub(super) struct Scanners<'a>(FxHashMap<(usize, usize), SpanScanner<'a>>);
+
+impl<'a> Scanners<'a> {
+    /// The scanner over `source`, created on first use.
+    pub(super) fn get(&mut self, source: &'a dyn SourceCode) -> &mut SpanScanner<'a> {
+        let data = source.data();
+        self.0
+            .entry((data.as_ptr().addr(), data.len()))
+            .or_insert_with(|| SpanScanner::for_source(source, 1, 1))
+    }
+
+    /// Starts the index of each source at the first label of `diagnostics`
+    /// in it, so labels rendered later never fall before the index and have
+    /// to be read without it.
+    pub(super) fn seed(&mut self, diagnostics: &[&'a dyn Diagnostic]) {
+        let mut firsts = FxHashMap::<_, (&'a dyn SourceCode, u32)>::default();
+        for &diagnostic in diagnostics {
+            let diagnostic_source = diagnostic.source_code();
+            for label in diagnostic.labels() {
+                let source = label.source_code().or(diagnostic_source);
+                let Some(source) = source.filter(|source| source.is_available()) else {
+                    continue;
+                };
+                let data = source.data();
+                let first = firsts
+                    .entry((data.as_ptr().addr(), data.len()))
+                    .or_insert((source, label.offset()));
+                first.1 = first.1.min(label.offset());
+            }
+        }
+        for (source, offset) in firsts.into_values() {
+            if offset as usize <= source.data().len() {
+                self.get(source).read_span((offset, 0).into());
+            }
+        }
+    }
+}
+
 impl GraphicalReportHandler {
-    pub(super) fn render_snippets(
+    pub(super) fn render_snippets<'a>(
         &self,
         f: &mut impl fmt::Write,
-        diagnostic: &dyn Diagnostic,
-        opt_source: Option<&dyn SourceCode>,
+        diagnostic: &'a dyn Diagnostic,
+        opt_source: Option<&'a dyn SourceCode>,
+        scanners: &mut Scanners<'a>,
+    ) -> fmt::Result {
+        match remap_labels(diagnostic.labels(), opt_source) {
+            Cow::Borrowed(labels) => self.render_labels(f, labels, opt_source, scanners),
+            // Remapped labels, and the sources they point into, only live
+            // for this call, so they cannot share the batch's scanners.
+            Cow::Owned(labels) => {
+                self.render_labels(f, &labels, opt_source, &mut Scanners::default())
+            }
+        }
+    }
+
+    fn render_labels<'a>(
+        &self,
+        f: &mut impl fmt::Write,
+        labels: &'a [LabeledSpan],
+        opt_source: Option<&'a dyn SourceCode>,
+        scanners: &mut Scanners<'a>,
     ) -> fmt::Result {
-        let Some(source) = opt_source else { return Ok(()) };
-        let labels = diagnostic.labels();
         if labels.is_empty() {
             return Ok(());
         }
-
-        // Share one forward scan across every span lookup below (one per label
-        // plus one per merge attempt).
-        let mut scanner = SpanScanner::new(source.data(), 1, 1);
-        let source_name = source.name();
-        let mut read = |span: &SourceSpan| scanner.read_span(*span);
+        if labels.iter().any(|label| label.source_code().is_some()) {
+            return self.render_grouped_snippets(f, labels, opt_source, scanners);
+        }
+        let Some(source) = opt_source.filter(|source| source.is_available()) else {
+            return Ok(());
+        };
 
         if let [label] = labels {
-            let contents = read(label.inner()).ok_or(fmt::Error)?;
-            return self.render_context(f, label, &contents, &[label], source_name);
+            return self.render_source_snippets(f, source, &[label], scanners);
         }
 
         let mut inline_labels = [&labels[0], &labels[1]];
@@ -56,6 +119,57 @@ impl GraphicalReportHandler {
             heap_labels.sort_unstable_by_key(|label| label.offset());
             heap_labels.as_slice()
         };
+        self.render_source_snippets(f, source, labels, scanners)
+    }
+
+    /// Renders labels that point into more than one source: one set of
+    /// contexts per source, starting with the diagnostic's own. Labels with
+    /// no source at all, or an unavailable one, are skipped.
+    fn render_grouped_snippets<'a>(
+        &self,
+        f: &mut impl fmt::Write,
+        labels: &'a [LabeledSpan],
+        opt_source: Option<&'a dyn SourceCode>,
+        scanners: &mut Scanners<'a>,
+    ) -> fmt::Result {
+        let mut groups: Vec<(&dyn SourceCode, Vec<&LabeledSpan>)> =
+            opt_source.map(|source| (source, Vec::new())).into_iter().collect();
+        for label in labels {
+            let Some(source) = label.source_code().or(opt_source) else { continue };
+            match groups.iter_mut().find(|(group, _)| ptr::addr_eq(*group, source)) {
+                Some((_, group_labels)) => group_labels.push(label),
+                None => groups.push((source, vec![label])),
+            }
+        }
+        for (source, mut labels) in groups {
+            if labels.is_empty() || !source.is_available() {
+                continue;
+            }
+            labels.sort_unstable_by_key(|label| label.offset());
+            self.render_source_snippets(f, source, &labels, scanners)?;
+        }
+        Ok(())
+    }
+
+    /// Renders `labels`, which all point into `source` and are sorted by
+    /// offset, merging overlapping snippets into shared contexts.
+    fn render_source_snippets<'a>(
+        &self,
+        f: &mut impl fmt::Write,
+        source: &'a dyn SourceCode,
+        labels: &[&LabeledSpan],
+        scanners: &mut Scanners<'a>,
+    ) -> fmt::Result {
+        // Share one forward scan across every span lookup below (one per label
+        // plus one per merge attempt), and with earlier reports of a batch.
+        let scanner = scanners.get(source);
+        let mut read = |span: &SourceSpan| scanner.read_span(*span);
+
+        if let [label] = labels {
+            let contents = read(label.inner()).ok_or(fmt::Error)?;
+            return self.render_context(f, label, &contents, labels, source);
+        }
+
         let mut contexts: Vec<(Cow<'_, LabeledSpan>, _)> = Vec::with_capacity(labels.len());
         for &right in labels {
             let right_conts = read(right.inner()).ok_or(fmt::Error)?;
@@ -88,7 +202,7 @@ impl GraphicalReportHandler {
             contexts.push((Cow::Borrowed(right), right_conts));
         }
         for (ctx, conts) in contexts {
-            self.render_context(f, &ctx, &conts, labels, source_name)?;
+            self.render_context(f, &ctx, &conts, labels, source)?;
         }
 
         Ok(())
@@ -100,9 +214,14 @@ impl GraphicalReportHandler {
         context: &LabeledSpan,
         contents: &SpanContents<'_>,
         labels: &[&LabeledSpan],
-        source_name: Option<&str>,
+        source: &dyn SourceCode,
     ) -> fmt::Result {
-        let lines = self.get_lines(contents);
+        // Fragments of a larger file are numbered by the file's lines.
+        let origin = source.origin();
+        let mut lines = self.get_lines(contents);
+        for line in &mut lines {
+            line.number += origin.line;
+        }
 
         // only consider labels from the context as primary label
         let ctx_labels = labels.iter().filter(|l| {
@@ -114,11 +233,21 @@ impl GraphicalReportHandler {
             ctx_labels.clone().find(|label| label.primary()).or_else(|| ctx_labels.clone().next());
 
         // sorting is your friend
+        let styles = &self.theme.styles;
+        let mut highlights = styles.highlights.iter().copied().cycle();
         let labels = labels
             .iter()
-            .copied()
-            .zip(self.theme.styles.highlights.iter().copied().cycle())
-            .map(|(label, st)| FancySpan::new(label.label(), *label.inner(), st))
+            .map(|label| {
+                let kind_style = match label.kind() {
+                    LabelKind::Primary => styles.primary,
+                    LabelKind::Secondary => highlights.next().unwrap_or_default(),
+                    LabelKind::Note => styles.note,
+                    LabelKind::Added => styles.added,
+                    LabelKind::Removed => styles.removed,
+                };
+                let style = LabeledSpan::style(label).unwrap_or(kind_style);
+                FancySpan::new(label.label(), *label.inner(), style, label.kind())
+            })
             .collect::<Vec<_>>();
 
         // The max number of gutter-lines that will be active at any given
@@ -155,18 +284,19 @@ impl GraphicalReportHandler {
             }
             None => (contents.line(), contents.column()),
         };
+        let primary = origin.to_parent(LineColumn::new(primary_line, primary_column));
 
-        match source_name {
+        match source.name() {
             Some(source_name) => {
                 let source_name = source_name.style(self.theme.styles.link);
-                writeln!(f, "[{}:{}:{}]", source_name, primary_line + 1, primary_column + 1)?;
+                writeln!(f, "[{}:{}:{}]", source_name, primary.line + 1, primary.column + 1)?;
             }
             _ => {
                 if lines.len() <= 1 {
                     write_repeated_char(f, self.theme.characters.hbar, 3)?;
                     f.write_char('\n')?;
                 } else {
-                    writeln!(f, "[{}:{}]", primary_line + 1, primary_column + 1)?;
+                    writeln!(f, "[{}:{}]", primary.line + 1, primary.column + 1)?;
                 }
             }
         }
@@ -1,12 +1,12 @@
 //! The span model used while drawing a snippet.
 //!
 //! A [`FancySpan`] is one of the diagnostic's labels paired with the [`Style`]
-//! it will be drawn in. Label text stays borrowed and is split into display
+//! it will be drawn in and its [`LabelKind`]. Label text stays borrowed and is split into display
 //! lines only when it is drawn.
 
 use owo_colors::Style;
 
-use crate::SourceSpan;
+use crate::{LabelKind, SourceSpan};
 
 /// How a label is being drawn on the current output line.
 ///
@@ -27,6 +27,7 @@ pub(super) struct FancySpan<'a> {
     label: Option<&'a str>,
     span: SourceSpan,
     pub(super) style: Style,
+    pub(super) kind: LabelKind,
 }
 
 impl PartialEq for FancySpan<'_> {
@@ -36,8 +37,13 @@ impl PartialEq for FancySpan<'_> {
 }
 
 impl<'a> FancySpan<'a> {
-    pub(super) fn new(label: Option<&'a str>, span: SourceSpan, style: Style) -> Self {
-        FancySpan { label, span, style }
+    pub(super) fn new(
+        label: Option<&'a str>,
+        span: SourceSpan,
+        style: Style,
+        kind: LabelKind,
+    ) -> Self {
+        FancySpan { label, span, style, kind }
     }
 
     pub(super) fn has_label(&self) -> bool {
@@ -0,0 +1,211 @@
+//! Diff-style rendering of [`Suggestion`]s.
+//!
+//! [`render_suggestions`](GraphicalReportHandler::render_suggestions) draws
+//! every line a suggestion touches twice: once as it is now, marked `-` with
+//! the replaced text highlighted, and once with the edit applied, marked `+`
+//! with the inserted text highlighted. The lines are read through the
+//! report's [`Scanners`], like the snippets above them. A suggestion that
+//! ends a line, such as one deleting or inserting whole lines, leaves out the
+//! unchanged line after it.
+
+use std::{fmt, ops::Range};
+
+use owo_colors::{OwoColorize, Style};
+
+use super::{handler::GraphicalReportHandler, label::write_repeated_char, snippet::Scanners};
+use crate::{Diagnostic, LineColumn, SourceCode, Suggestion, source::reader::SpanContents};
+
+/// One side of a suggestion's diff: the lines as they are now, or with the
+/// edit applied.
+struct DiffSide<'a> {
+    marker: char,
+    style: Style,
+    text: &'a str,
+    /// The bytes of `text` to draw with `style`.
+    highlight: Range<usize>,
+}
+
+/// The whole lines a suggestion touches, as they are now and with the edit
+/// applied.
+struct Layout<'a> {
+    /// The 0-based line and byte column the suggestion starts at.
+    position: LineColumn,
+    old: &'a str,
+    new: String,
+    /// The bytes of `old` the suggestion replaces.
+    removed: Range<usize>,
+    /// The bytes of `new` that replace them.
+    added: Range<usize>,
+}
+
+impl<'a> Layout<'a> {
+    /// Lays `suggestion` out over `contents`, which holds its span with the
+    /// lines around it. `None` when the span splits a character.
+    fn new(suggestion: &Suggestion, contents: &SpanContents<'a>) -> Option<Self> {
+        let text = std::str::from_utf8(contents.data()).ok()?;
+        let start =
+            (suggestion.inner().offset() as usize).checked_sub(contents.span().offset() as usize)?;
+        let end = start + suggestion.inner().len() as usize;
+        let (head, tail) = (text.get(..start)?, text.get(end..)?);
+        let line_start = head.rfind('\n').map_or(0, |i| i + 1);
+        let before = &text[line_start..start];
+        let replacement = suggestion.replacement();
+        // An edit that ends at the start of a line, and leaves the text
+        // before it ending a line too, such as a deleted or inserted line,
+        // leaves the line after it as it is.
+        let ends_line = |text: &str| text.is_empty() || text.ends_with('\n');
+        let line_end = if ends_line(&text[..end]) && ends_line(&[before, replacement].concat()) {
+            end
+        } else {
+            tail.find('\n').map_or(text.len(), |i| end + i + 1)
+        };
+        let old = &text[line_start..line_end];
+        let after = &text[end..line_end];
+        let line = contents.line() + head[..line_start].matches('\n').count();
+        Some(Self {
+            position: LineColumn::new(line, start - line_start),
+            old,
+            new: [before, replacement, after].concat(),
+            removed: before.len()..end - line_start,
+            added: before.len()..before.len() + replacement.len(),
+        })
+    }
+}
+
+impl GraphicalReportHandler {
+    /// Renders each of [`Diagnostic::suggestions`] as a diff. Suggestions
+    /// that cannot be read from `opt_source`, because they end past it or
+    /// split a character, are drawn as their message alone.
+    pub(super) fn render_suggestions<'a>(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+        opt_source: Option<&'a dyn SourceCode>,
+        scanners: &mut Scanners<'a>,
+    ) -> fmt::Result {
+        let Some(source) = opt_source.filter(|source| source.is_available()) else {
+            return Ok(());
+        };
+        for suggestion in diagnostic.suggestions() {
+            if let Some(message) = suggestion.message() {
+                let width = self.termwidth.saturating_sub(4);
+                let initial_indent = "  suggestion: ".style(self.theme.styles.help).to_string();
+                let opts = Self::wrap_options(width, &initial_indent, "              ");
+                Self::write_fill(f, message, opts)?;
+                f.write_char('\n')?;
+            }
+            let contents = scanners.get(source).read_span(*suggestion.inner());
+            if let Some(layout) =
+                contents.as_ref().and_then(|contents| Layout::new(suggestion, contents))
+            {
+                self.render_layout(f, source, &layout)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws the diff of `layout` under a header naming where it starts.
+    fn render_layout(
+        &self,
+        f: &mut impl fmt::Write,
+        source: &dyn SourceCode,
+        layout: &Layout<'_>,
+    ) -> fmt::Result {
+        // Fragments of a larger file are numbered by the file's lines.
+        let LineColumn { line, column } = source.origin().to_parent(layout.position);
+        let line_count = diff_lines(layout.old).count().max(diff_lines(&layout.new).count());
+        let last_line = line + line_count.max(1);
+        let linum_width = last_line.checked_ilog10().map_or(1, |width| width as usize + 1);
+
+        write_repeated_char(f, ' ', linum_width + 2)?;
+        f.write_char(self.theme.characters.ltop)?;
+        f.write_char(self.theme.characters.hbar)?;
+        match source.name() {
+            Some(source_name) => {
+                let source_name = source_name.style(self.theme.styles.link);
+                writeln!(f, "[{}:{}:{}]", source_name, line + 1, column + 1)?;
+            }
+            None => writeln!(f, "[{}:{}]", line + 1, column + 1)?,
+        }
+        let styles = &self.theme.styles;
+        let removed = layout.removed.clone();
+        let added = layout.added.clone();
+        let old =
+            DiffSide { marker: '-', style: styles.removed, text: layout.old, highlight: removed };
+        let new =
+            DiffSide { marker: '+', style: styles.added, text: &layout.new, highlight: added };
+        self.render_diff_lines(f, linum_width, line, &old)?;
+        self.render_diff_lines(f, linum_width, line, &new)?;
+        write_repeated_char(f, ' ', linum_width + 2)?;
+        f.write_char(self.theme.characters.lbot)?;
+        write_repeated_char(f, self.theme.characters.hbar, 4)?;
+        f.write_char('\n')
+    }
+
+    /// Writes the text of `side` line by line behind its marker.
+    fn render_diff_lines(
+        &self,
+        f: &mut impl fmt::Write,
+        linum_width: usize,
+        first_line: usize,
+        side: &DiffSide<'_>,
+    ) -> fmt::Result {
+        let DiffSide { marker, style, text, ref highlight } = *side;
+        for (i, (line_offset, line)) in diff_lines(text).enumerate() {
+            let clamp = |offset: usize| offset.clamp(line_offset, line_offset + line.len());
+            let line_highlight =
+                clamp(highlight.start) - line_offset..clamp(highlight.end) - line_offset;
+
+            self.write_linum(f, linum_width, first_line + i + 1)?;
+            write!(f, "{} ", marker.style(style))?;
+            Self::write_highlighted(f, line, line_highlight, style)?;
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+
+    /// Writes a line of text with `highlight` styled, replacing tabs with
+    /// spaces like [`Self::render_line_text`].
+    fn write_highlighted(
+        f: &mut impl fmt::Write,
+        text: &str,
+        highlight: Range<usize>,
+        style: Style,
+    ) -> fmt::Result {
+        if !text.contains('\t') {
+            let highlighted = &text[highlight.clone()];
+            f.write_str(&text[..highlight.start])?;
+            write!(f, "{}", highlighted.style(style))?;
+            return f.write_str(&text[highlight.end..]);
+        }
+
+        let mut segments = [String::new(), String::new(), String::new()];
+        for ((i, c), width) in text.char_indices().zip(Self::line_visual_char_width(text)) {
+            let segment = if i < highlight.start {
+                &mut segments[0]
+            } else if i < highlight.end {
+                &mut segments[1]
+            } else {
+                &mut segments[2]
+            };
+            if c == '\t' {
+                segment.extend(std::iter::repeat_n(' ', width));
+            } else {
+                segment.push(c);
+            }
+        }
+        let [before, highlighted, after] = segments;
+        write!(f, "{before}{}{after}", highlighted.style(style))
+    }
+}
+
+/// The lines of `text` with their offsets, without their line breaks. A
+/// final line break ends the last line instead of starting an empty one.
+fn diff_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        Some((start, line.strip_suffix('\r').unwrap_or(line)))
+    })
+}
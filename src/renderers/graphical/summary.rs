@@ -0,0 +1,69 @@
+//! The totals line printed after a batch of reports.
+
+use std::fmt;
+
+use owo_colors::OwoColorize;
+
+use super::handler::GraphicalReportHandler;
+use crate::{DiagnosticSummary, Severity};
+
+impl GraphicalReportHandler {
+    /// Render the totals of a batch of reports, such as
+    /// `Found 3 errors and 12 warnings in 4 files.`, followed by the most
+    /// frequent codes when the summary lists any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered summary fails.
+    pub fn render_summary(
+        &self,
+        f: &mut impl fmt::Write,
+        summary: &DiagnosticSummary,
+    ) -> fmt::Result {
+        let styles = &self.theme.styles;
+        let counts: Vec<_> = [
+            (Severity::Error, "error", "errors", styles.error),
+            (Severity::Warning, "warning", "warnings", styles.warning),
+            (Severity::Advice, "advice", "advice", styles.advice),
+        ]
+        .into_iter()
+        .filter_map(|(severity, one, many, style)| {
+            let count = summary.count(severity);
+            (count > 0).then_some((count, if count == 1 { one } else { many }, style))
+        })
+        .collect();
+
+        if counts.is_empty() {
+            f.write_str("No problems found")?;
+        } else {
+            f.write_str("Found ")?;
+            for (i, (count, noun, style)) in counts.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(if i + 1 == counts.len() { " and " } else { ", " })?;
+                }
+                if style.is_plain() {
+                    write!(f, "{count} {noun}")?;
+                } else {
+                    write!(f, "{}", format_args!("{count} {noun}").style(*style))?;
+                }
+            }
+        }
+        match summary.files() {
+            0 => {}
+            1 => f.write_str(" in 1 file")?,
+            files => write!(f, " in {files} files")?,
+        }
+        f.write_str(".\n")?;
+
+        let codes = summary.top_codes();
+        if let Some((_, most)) = codes.first() {
+            // The most frequent code comes first, so its count is the widest.
+            let width = most.to_string().len();
+            f.write_str("Most frequent codes:\n")?;
+            for (code, count) in codes {
+                writeln!(f, "  {count:>width$} {code}")?;
+            }
+        }
+        Ok(())
+    }
+}
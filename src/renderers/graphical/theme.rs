@@ -5,6 +5,8 @@ use std::{
 
 use owo_colors::Style;
 
+use crate::LabelKind;
+
 /// Theme used by [`GraphicalReportHandler`](crate::GraphicalReportHandler).
 ///
 /// Use one of the predefined constructors below.
@@ -83,7 +85,14 @@ pub(crate) struct ThemeStyles {
     pub(crate) note: Style,
     pub(crate) link: Style,
     pub(crate) linum: Style,
+    /// Labels of [`LabelKind::Primary`].
+    pub(crate) primary: Style,
+    /// Labels of [`LabelKind::Secondary`], cycled through in order.
     pub(crate) highlights: [Style; 3],
+    /// Text inserted by a suggestion, and labels of [`LabelKind::Added`].
+    pub(crate) added: Style,
+    /// Text removed by a suggestion, and labels of [`LabelKind::Removed`].
+    pub(crate) removed: Style,
 }
 
 fn style() -> Style {
@@ -100,11 +109,14 @@ impl ThemeStyles {
             note: style().fg_rgb::<106, 159, 181>(),
             link: style().fg_rgb::<92, 157, 255>().bold(),
             linum: style().dimmed(),
+            primary: style().fg_rgb::<246, 87, 248>().bold(),
             highlights: [
                 style().fg_rgb::<246, 87, 248>(),
                 style().fg_rgb::<30, 201, 212>(),
                 style().fg_rgb::<145, 246, 111>(),
             ],
+            added: style().fg_rgb::<145, 246, 111>(),
+            removed: style().fg_rgb::<225, 80, 80>(),
         }
     }
 
@@ -117,7 +129,10 @@ impl ThemeStyles {
             note: style(),
             link: style(),
             linum: style(),
+            primary: style(),
             highlights: [style(); 3],
+            added: style(),
+            removed: style(),
         }
     }
 }
@@ -145,6 +160,12 @@ pub(crate) struct ThemeCharacters {
     pub(crate) underbar: char,
     pub(crate) underline: char,
 
+    pub(crate) primary_underbar: char,
+    pub(crate) primary_underline: char,
+    pub(crate) note_underline: char,
+    pub(crate) added_underline: char,
+    pub(crate) removed_underline: char,
+
     pub(crate) error: &'static str,
     pub(crate) warning: &'static str,
     pub(crate) advice: &'static str,
@@ -164,6 +185,11 @@ impl ThemeCharacters {
             rcross: '┤',
             underbar: '┬',
             underline: '─',
+            primary_underbar: '┯',
+            primary_underline: '━',
+            note_underline: '┄',
+            added_underline: '+',
+            removed_underline: '-',
             error: "×",
             warning: "⚠",
             advice: "☞",
@@ -183,9 +209,37 @@ impl ThemeCharacters {
             rcross: '|',
             underbar: '|',
             underline: '^',
+            primary_underbar: '^',
+            primary_underline: '~',
+            note_underline: '.',
+            added_underline: '+',
+            removed_underline: '-',
             error: "x",
             warning: "!",
             advice: ">",
         }
     }
 }
+
+impl ThemeCharacters {
+    /// The character a label of `kind` is underlined with.
+    pub(crate) const fn underline_for(&self, kind: LabelKind) -> char {
+        match kind {
+            LabelKind::Primary => self.primary_underline,
+            LabelKind::Secondary => self.underline,
+            LabelKind::Note => self.note_underline,
+            LabelKind::Added => self.added_underline,
+            LabelKind::Removed => self.removed_underline,
+        }
+    }
+
+    /// The character joining a label of `kind` to its text.
+    pub(crate) const fn underbar_for(&self, kind: LabelKind) -> char {
+        match kind {
+            LabelKind::Primary => self.primary_underbar,
+            LabelKind::Secondary | LabelKind::Note | LabelKind::Added | LabelKind::Removed => {
+                self.underbar
+            }
+        }
+    }
+}
@@ -1,6 +1,12 @@
 use std::fmt::{self, Write};
 
-use crate::{Severity, protocol::Diagnostic, source::reader::SpanScanner};
+use super::causes::Causes;
+use crate::{
+    DiagnosticSummary, LineColumn, Severity, SourceCode, SourceOrigin, SourceSpan,
+    protocol::Diagnostic,
+    renderers::ReportHandler,
+    source::{reader::SpanScanner, remap_labels},
+};
 
 /**
 Renders diagnostics as machine-readable JSON.
@@ -23,7 +29,7 @@ impl Default for JSONReportHandler {
     }
 }
 
-struct Escape<'a>(&'a str);
+pub(super) struct Escape<'a>(&'a str);
 
 impl fmt::Display for Escape<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -40,6 +46,8 @@ impl fmt::Display for Escape<'_> {
             };
             if let Some(escape) = escape {
                 f.write_str(escape)?;
+            } else if c < ' ' {
+                write!(f, "\\u{:04x}", u32::from(c))?;
             } else {
                 f.write_char(c)?;
             }
@@ -48,7 +56,7 @@ impl fmt::Display for Escape<'_> {
     }
 }
 
-const fn escape(input: &'_ str) -> Escape<'_> {
+pub(super) const fn escape(input: &'_ str) -> Escape<'_> {
     Escape(input)
 }
 
@@ -63,6 +71,43 @@ impl JSONReportHandler {
         &self,
         f: &mut impl fmt::Write,
         diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        Self::render_diagnostic(f, diagnostic, None)
+    }
+
+    /// Render the totals of a batch of reports as an object with the same
+    /// counts and codes as
+    /// [`GraphicalReportHandler::render_summary`](crate::GraphicalReportHandler::render_summary).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered summary fails.
+    #[expect(clippy::unused_self, reason = "keeps a consistent renderer API")]
+    pub fn render_summary(
+        &self,
+        f: &mut impl fmt::Write,
+        summary: &DiagnosticSummary,
+    ) -> fmt::Result {
+        write!(f, r#"{{"errors": {},"#, summary.count(Severity::Error))?;
+        write!(f, r#""warnings": {},"#, summary.count(Severity::Warning))?;
+        write!(f, r#""advice": {},"#, summary.count(Severity::Advice))?;
+        write!(f, r#""files": {},"#, summary.files())?;
+        write!(f, r#""codes": ["#)?;
+        for (i, (code, count)) in summary.top_codes().iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, r#"{{"code": "{}","count": {count}}}"#, escape(code))?;
+        }
+        write!(f, "]}}")
+    }
+
+    /// Renders one diagnostic object. Related diagnostics without their own
+    /// source code are rendered against `parent_src`.
+    fn render_diagnostic(
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+        parent_src: Option<&dyn SourceCode>,
     ) -> fmt::Result {
         write!(f, r#"{{"message": "{}","#, escape(&diagnostic.to_string()))?;
         if let Some(code) = diagnostic.code() {
@@ -74,8 +119,16 @@ impl JSONReportHandler {
             Some(Severity::Advice) => "advice",
         };
         write!(f, r#""severity": "{severity:}","#)?;
+        write!(f, r#""causes": ["#)?;
+        for (i, cause) in Causes::new(diagnostic).enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, r#""{}""#, escape(&cause.to_string()))?;
+        }
+        write!(f, "],")?;
         if let Some(url) = diagnostic.url() {
-            write!(f, r#""url": "{url}","#)?;
+            write!(f, r#""url": "{}","#, escape(&url))?;
         }
         if let Some(help) = diagnostic.help() {
             write!(f, r#""help": "{}","#, escape(&help))?;
@@ -83,15 +136,18 @@ impl JSONReportHandler {
         if let Some(note) = diagnostic.note() {
             write!(f, r#""note": "{}","#, escape(&note))?;
         }
-        let source = diagnostic.source_code();
+        let source = diagnostic.source_code().or(parent_src);
         if let Some(source) = source {
             write!(f, r#""filename": "{}","#, escape(source.name().unwrap_or_default()))?;
         }
         {
             write!(f, r#""labels": ["#)?;
-            let mut scanner = source.map(|source| SpanScanner::new(source.data(), 0, 0));
+            let mut scanner = source
+                .filter(|source| source.is_available())
+                .map(|source| SpanScanner::for_source(source, 0, 0));
+            let origin = source.map(SourceCode::origin).unwrap_or_default();
             let mut add_comma = false;
-            for label in diagnostic.labels() {
+            for label in &*remap_labels(diagnostic.labels(), source) {
                 if add_comma {
                     write!(f, ",")?;
                 } else {
@@ -101,29 +157,101 @@ impl JSONReportHandler {
                 if let Some(label_name) = label.label() {
                     write!(f, r#""label": "{}","#, escape(label_name))?;
                 }
-                write!(f, r#""span": {{"#)?;
-                write!(f, r#""offset": {},"#, label.offset())?;
-                write!(f, r#""length": {},"#, label.len())?;
-
-                if let Some(location) =
-                    scanner.as_mut().and_then(|scanner| scanner.read_span(*label.inner()))
-                {
-                    write!(f, r#""line": {},"#, location.line() + 1)?;
-                    write!(f, r#""column": {}"#, location.column() + 1)?;
+                write!(f, r#""kind": "{}","#, label.kind().as_str())?;
+                if let Some(label_source) = label.source_code() {
+                    let name = label_source.name().unwrap_or_default();
+                    write!(f, r#""filename": "{}","#, escape(name))?;
+                    let mut label_scanner = label_source
+                        .is_available()
+                        .then(|| SpanScanner::for_source(label_source, 0, 0));
+                    let label_origin = label_source.origin();
+                    Self::render_span(f, label_scanner.as_mut(), *label.inner(), label_origin)?;
                 } else {
-                    write!(f, r#""line": null,"column": null"#)?;
+                    if let Some(source) = source {
+                        let name = source.name().unwrap_or_default();
+                        write!(f, r#""filename": "{}","#, escape(name))?;
+                    }
+                    Self::render_span(f, scanner.as_mut(), *label.inner(), origin)?;
                 }
-
-                write!(f, "}}}}")?;
+                write!(f, "}}")?;
+            }
+            write!(f, "]")?;
+            write!(f, r#","suggestions": ["#)?;
+            for (i, suggestion) in diagnostic.suggestions().iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{{")?;
+                if let Some(message) = suggestion.message() {
+                    write!(f, r#""message": "{}","#, escape(message))?;
+                }
+                write!(f, r#""replacement": "{}","#, escape(suggestion.replacement()))?;
+                write!(f, r#""applicability": "{}","#, suggestion.applicability().as_str())?;
+                Self::render_span(f, scanner.as_mut(), *suggestion.inner(), origin)?;
+                write!(f, "}}")?;
             }
             write!(f, "]")?;
         }
+        write!(f, r#","related": ["#)?;
+        for (i, related) in diagnostic.related().iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            Self::render_diagnostic(f, &**related, source)?;
+        }
+        write!(f, "]}}")
+    }
+
+    /// Renders a `"span"` member with 1-based line and column, or `null`s
+    /// when the span cannot be located in the source. Positions are in the
+    /// file the source starts at `origin` in.
+    fn render_span(
+        f: &mut impl fmt::Write,
+        scanner: Option<&mut SpanScanner<'_>>,
+        span: SourceSpan,
+        origin: SourceOrigin,
+    ) -> fmt::Result {
+        write!(f, r#""span": {{"#)?;
+        write!(f, r#""offset": {},"#, u64::from(origin.offset) + u64::from(span.offset()))?;
+        write!(f, r#""length": {},"#, span.len())?;
+        if let Some(location) = scanner.and_then(|scanner| scanner.read_span(span)) {
+            let location = LineColumn::new(location.line(), location.column());
+            let LineColumn { line, column } = origin.to_parent(location);
+            write!(f, r#""line": {},"#, line + 1)?;
+            write!(f, r#""column": {}"#, column + 1)?;
+        } else {
+            write!(f, r#""line": null,"column": null"#)?;
+        }
         write!(f, "}}")
     }
 }
 
+/// Batches are rendered as an array of report objects.
+impl ReportHandler for JSONReportHandler {
+    fn render_report(
+        &self,
+        mut f: &mut dyn fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        Self::render_report(self, &mut f, diagnostic)
+    }
+
+    fn begin_batch(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        f.write_str("[")
+    }
+
+    fn between_reports(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        f.write_str(",")
+    }
+
+    fn end_batch(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        f.write_str("]")
+    }
+}
+
 #[test]
 fn test_escape() {
     assert_eq!(escape("a\nb").to_string(), r"a\nb");
     assert_eq!(escape("C:\\Miette").to_string(), r"C:\\Miette");
+    assert_eq!(escape("\u{1}\u{7f}").to_string(), "\\u0001\u{7f}");
 }
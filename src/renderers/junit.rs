@@ -0,0 +1,250 @@
+use std::fmt::{self, Write};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::xml::escape;
+use crate::{
+    GraphicalReportHandler, GraphicalTheme, LabeledSpan, Severity, SourceCode,
+    protocol::Diagnostic, renderers::ReportHandler, source::remap_labels,
+};
+
+/**
+Renders batches of diagnostics as JUnit XML, for CI systems that only show
+test results.
+
+Diagnostics are grouped into a `<testsuite>` per file, the
+[`SourceCode::name`] of their primary label, or of their first label if
+none is primary. Each error or warning is a failing `<testcase>` named after
+its [`Diagnostic::code`], or its message if it has none, with the report
+[`GraphicalReportHandler`] renders without colors in its `<failure>`. When
+that report cannot be rendered, such as for a label outside its source, the
+message, help and note are used instead. Advice is left out.
+
+Files given to [`JUnitReportHandler::with_files`] that have no errors or
+warnings get a passing test case, so the test count reflects every checked
+file.
+
+# Examples
+```
+use std::sync::Arc;
+
+use miette::{JUnitReportHandler, LabeledSpan, MietteDiagnostic, NamedSource};
+
+let source = Arc::new(NamedSource::new("src/main.js", String::from("var x = 1;\n")));
+let diagnostic = MietteDiagnostic::new("unexpected `var`")
+    .with_code("no-var")
+    .with_label(LabeledSpan::at(0..3, "declared here"))
+    .with_source_code(source);
+
+let mut output = String::new();
+JUnitReportHandler::new()
+    .with_files(["src/main.js", "src/lib.js"])
+    .render_reports(&mut output, &[&diagnostic])
+    .unwrap();
+assert!(output.contains(r#"<testcase name="no-var" classname="src/main.js">"#));
+assert!(output.contains(r#"<testcase name="src/lib.js" classname="src/lib.js"/>"#));
+```
+*/
+#[derive(Debug, Clone)]
+pub struct JUnitReportHandler {
+    graphical: GraphicalReportHandler,
+    files: Vec<String>,
+}
+
+impl JUnitReportHandler {
+    /// Create a new [`JUnitReportHandler`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            graphical: GraphicalReportHandler::new_themed(GraphicalTheme::none()).with_links(false),
+            files: Vec::new(),
+        }
+    }
+
+    /// Sets the width to wrap the embedded reports at.
+    #[must_use]
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.graphical = self.graphical.with_width(width);
+        self
+    }
+
+    /// Sets every file that was checked, so files without errors or warnings
+    /// are listed as passing.
+    #[must_use]
+    pub fn with_files(mut self, files: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.files = files.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Render a document with a single [`Diagnostic`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered document fails.
+    pub fn render_report(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        self.render_reports(f, &[diagnostic])
+    }
+
+    /// Render a document with every diagnostic of the batch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered document fails.
+    pub fn render_reports(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> fmt::Result {
+        let mut suites = Vec::<(String, Vec<&dyn Diagnostic>)>::new();
+        let mut suite_indices = FxHashMap::<String, usize>::default();
+        for &diagnostic in diagnostics {
+            if diagnostic.severity() == Some(Severity::Advice) {
+                continue;
+            }
+            let name = file_name(diagnostic);
+            let index = *suite_indices.entry(name).or_insert_with_key(|name| {
+                suites.push((name.clone(), Vec::new()));
+                suites.len() - 1
+            });
+            suites[index].1.push(diagnostic);
+        }
+        let mut listed = FxHashSet::default();
+        let passing: Vec<_> = self
+            .files
+            .iter()
+            .filter(|file| !suite_indices.contains_key(*file) && listed.insert(*file))
+            .collect();
+        let failures: usize = suites.iter().map(|(_, failures)| failures.len()).sum();
+
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            f,
+            r#"<testsuites tests="{}" failures="{failures}" errors="0">"#,
+            failures + passing.len()
+        )?;
+        let mut report = String::new();
+        for (name, failures) in &suites {
+            let name = escape(name);
+            let count = failures.len();
+            writeln!(
+                f,
+                r#"<testsuite name="{name}" tests="{count}" failures="{count}" errors="0">"#
+            )?;
+            for &diagnostic in failures {
+                let message = diagnostic.to_string();
+                let test = diagnostic.code().map_or_else(|| message.clone(), Into::into);
+                let kind = match diagnostic.severity() {
+                    Some(Severity::Warning) => "warning",
+                    _ => "error",
+                };
+                report.clear();
+                if self.graphical.render_report(&mut report, diagnostic).is_err() {
+                    report.clear();
+                    plain_report(&mut report, &message, diagnostic)?;
+                }
+                writeln!(f, r#"<testcase name="{}" classname="{name}">"#, escape(&test))?;
+                write!(f, r#"<failure message="{}" type="{kind}">"#, escape(&message))?;
+                writeln!(f, "{}</failure>", Cdata(&report))?;
+                writeln!(f, "</testcase>")?;
+            }
+            writeln!(f, "</testsuite>")?;
+        }
+        for file in passing {
+            let name = escape(file);
+            writeln!(f, r#"<testsuite name="{name}" tests="1" failures="0" errors="0">"#)?;
+            writeln!(f, r#"<testcase name="{name}" classname="{name}"/>"#)?;
+            writeln!(f, "</testsuite>")?;
+        }
+        writeln!(f, "</testsuites>")
+    }
+}
+
+impl Default for JUnitReportHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The file of the primary label, or of the first label if none is primary,
+/// or else of the diagnostic's source code. Empty when it has no name.
+fn file_name(diagnostic: &dyn Diagnostic) -> String {
+    let source = diagnostic.source_code();
+    let labels = remap_labels(diagnostic.labels(), source);
+    let label = labels.iter().find(|label| label.primary()).or_else(|| labels.first());
+    let source = label.and_then(LabeledSpan::source_code).or(source);
+    source.and_then(SourceCode::name).unwrap_or_default().to_owned()
+}
+
+/// The message, help and note of a diagnostic whose graphical report could
+/// not be rendered.
+fn plain_report(
+    f: &mut impl fmt::Write,
+    message: &str,
+    diagnostic: &dyn Diagnostic,
+) -> fmt::Result {
+    f.write_str(message)?;
+    if let Some(help) = diagnostic.help() {
+        write!(f, "\n\nhelp: {help}")?;
+    }
+    if let Some(note) = diagnostic.note() {
+        write!(f, "\n\nnote: {note}")?;
+    }
+    Ok(())
+}
+
+/// Text in CDATA sections. A `]]>` in the text ends one section and starts
+/// the next between its `]]` and `>`, and characters XML 1.0 cannot hold
+/// are replaced with U+FFFD.
+struct Cdata<'a>(&'a str);
+
+impl fmt::Display for Cdata<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<![CDATA[")?;
+        for (i, part) in self.0.split("]]>").enumerate() {
+            if i > 0 {
+                f.write_str("]]]]><![CDATA[>")?;
+            }
+            for c in part.chars() {
+                if (c < ' ' && !matches!(c, '\t' | '\n' | '\r'))
+                    || matches!(c, '\u{fffe}' | '\u{ffff}')
+                {
+                    f.write_char(char::REPLACEMENT_CHARACTER)?;
+                } else {
+                    f.write_char(c)?;
+                }
+            }
+        }
+        f.write_str("]]>")
+    }
+}
+
+/// Batches are rendered as one document, and a single report as a document
+/// of its own.
+impl ReportHandler for JUnitReportHandler {
+    fn render_report(
+        &self,
+        mut f: &mut dyn fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        Self::render_report(self, &mut f, diagnostic)
+    }
+
+    fn render_reports(
+        &self,
+        mut f: &mut dyn fmt::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> fmt::Result {
+        Self::render_reports(self, &mut f, diagnostics)
+    }
+}
+
+#[test]
+fn test_cdata() {
+    assert_eq!(Cdata("a]]>b").to_string(), "<![CDATA[a]]]]><![CDATA[>b]]>");
+    assert_eq!(Cdata("]]>]]>").to_string(), "<![CDATA[]]]]><![CDATA[>]]]]><![CDATA[>]]>");
+    assert_eq!(Cdata("\t\u{1b}[31m").to_string(), "<![CDATA[\t\u{fffd}[31m]]>");
+}
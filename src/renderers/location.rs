@@ -0,0 +1,90 @@
+//! Where diagnostics point, for the renderers of machine-readable formats.
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    ColumnUnit, Diagnostic, LineColumn, SourceCode, SourceSpan,
+    source::{column_count, reader::SpanScanner},
+};
+
+/// Where a span starts and ends, 0-based, in the file its source was taken
+/// from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Location {
+    pub start: LineColumn,
+    pub end: LineColumn,
+}
+
+/// One [`SpanScanner`] per source, shared by every report of a batch, for
+/// renderers that only need where spans are. Sources are identified by the
+/// address and length of their data.
+#[derive(Default)]
+pub(super) struct Locator<'a>(FxHashMap<(usize, usize), SpanScanner<'a>>);
+
+impl<'a> Locator<'a> {
+    /// Where `span` is in `source`, with columns counted in `unit`, or
+    /// `None` when the source is not available or the span is out of
+    /// bounds.
+    ///
+    /// Positions are read with the same [`SpanScanner`] as
+    /// [`JSONReportHandler`](crate::JSONReportHandler) uses, so every format
+    /// agrees on them. The column a fragment starts at counts bytes
+    /// whatever the unit, as in [`SourceOrigin`](crate::SourceOrigin).
+    pub(super) fn locate(
+        &mut self,
+        source: &'a dyn SourceCode,
+        span: SourceSpan,
+        unit: ColumnUnit,
+    ) -> Option<Location> {
+        if !source.is_available() {
+            return None;
+        }
+        let data = source.data();
+        let scanner = self.0.entry((data.as_ptr().addr(), data.len())).or_insert_with(|| {
+            let mut scanner = SpanScanner::for_source(source, 0, 0);
+            // Index from the start, so spans rendered after later ones are
+            // still looked up in the index.
+            scanner.read_span(SourceSpan::from((0, 0)));
+            scanner
+        });
+        let end = span.offset().checked_add(span.len())?;
+        let origin = source.origin();
+        let start = position(scanner, data, span.offset(), unit)?;
+        let end = position(scanner, data, end, unit)?;
+        Some(Location { start: origin.to_parent(start), end: origin.to_parent(end) })
+    }
+}
+
+/// The position of byte `offset` in `data`.
+fn position(
+    scanner: &mut SpanScanner<'_>,
+    data: &[u8],
+    offset: u32,
+    unit: ColumnUnit,
+) -> Option<LineColumn> {
+    let contents = scanner.read_span(SourceSpan::from((offset, 0)))?;
+    let mut position = LineColumn::new(contents.line(), contents.column());
+    if unit != ColumnUnit::Byte {
+        // Inside a `\r\n` the column is that of the `\r`.
+        let mut offset = offset as usize;
+        if offset > 0 && data.get(offset - 1..=offset) == Some(b"\r\n") {
+            offset -= 1;
+        }
+        position.column = column_count(&data[offset - position.column..offset], unit);
+    }
+    Some(position)
+}
+
+/// Lists `diagnostic` and its related diagnostics, each with the source
+/// code its labels point into.
+pub(super) fn flatten<'a>(
+    results: &mut Vec<(&'a dyn Diagnostic, Option<&'a dyn SourceCode>)>,
+    diagnostic: &'a dyn Diagnostic,
+    parent_src: Option<&'a dyn SourceCode>,
+) {
+    let source = diagnostic.source_code().or(parent_src);
+    results.push((diagnostic, source));
+    for related in diagnostic.related() {
+        flatten(results, &**related, source);
+    }
+}
@@ -1,7 +1,22 @@
 //! Diagnostic renderers included with `miette`.
 
+pub use checkstyle::CheckstyleReportHandler;
+pub use code_quality::{CodeQualityReportHandler, CodeQualitySeverity};
+pub use github::GitHubActionsReportHandler;
 pub use graphical::*;
 pub use json::*;
+pub use junit::JUnitReportHandler;
+pub use report_handler::ReportHandler;
+pub use sarif::SarifReportHandler;
 
+mod causes;
+mod checkstyle;
+mod code_quality;
+mod github;
 mod graphical;
 mod json;
+mod junit;
+mod location;
+mod report_handler;
+mod sarif;
+mod xml;
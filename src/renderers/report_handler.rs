@@ -0,0 +1,165 @@
+use std::{fmt, io};
+
+use crate::Diagnostic;
+
+/// A renderer chosen at runtime, such as from a `--format` flag.
+///
+/// The trait is object safe, so applications can hold a
+/// `Box<dyn ReportHandler>` instead of matching on every renderer. Formats
+/// that wrap a batch of reports in an array or document write the wrapper
+/// from the batch hooks, which [`ReportHandler::render_reports`] calls
+/// around the reports.
+///
+/// # Examples
+/// ```
+/// use miette::{
+///     Diagnostic, GraphicalReportHandler, GraphicalTheme, JSONReportHandler, MietteDiagnostic,
+///     ReportHandler,
+/// };
+///
+/// let handler: Box<dyn ReportHandler> = if std::env::args().any(|arg| arg == "--json") {
+///     Box::new(JSONReportHandler::new())
+/// } else {
+///     Box::new(GraphicalReportHandler::new_themed(GraphicalTheme::none()))
+/// };
+/// let diagnostic = MietteDiagnostic::new("unexpected token");
+/// let mut output = String::new();
+/// handler.render_reports(&mut output, &[&diagnostic]).unwrap();
+/// ```
+pub trait ReportHandler {
+    /// Render a [`Diagnostic`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered report fails.
+    fn render_report(&self, f: &mut dyn fmt::Write, diagnostic: &dyn Diagnostic) -> fmt::Result;
+
+    /// Called once before the reports of a batch. Writes nothing by default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing fails.
+    fn begin_batch(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        let _ = f;
+        Ok(())
+    }
+
+    /// Called between two reports of a batch. Writes nothing by default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing fails.
+    fn between_reports(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        let _ = f;
+        Ok(())
+    }
+
+    /// Called once after the reports of a batch. Writes nothing by default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing fails.
+    fn end_batch(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        let _ = f;
+        Ok(())
+    }
+
+    /// Render a batch of diagnostics, with the batch hooks around and
+    /// between them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered reports fails.
+    fn render_reports(
+        &self,
+        f: &mut dyn fmt::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> fmt::Result {
+        self.begin_batch(f)?;
+        for (i, diagnostic) in diagnostics.iter().enumerate() {
+            if i > 0 {
+                self.between_reports(f)?;
+            }
+            self.render_report(f, *diagnostic)?;
+        }
+        self.end_batch(f)
+    }
+
+    /// Render a [`Diagnostic`] straight into `w`, such as a
+    /// [`BufWriter`](io::BufWriter) around a locked stdout, instead of into a
+    /// `String` first. `w` is not flushed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first write to `w` that fails, or an error
+    /// of kind [`io::ErrorKind::InvalidData`] when rendering fails for
+    /// another reason.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{self, BufWriter, Write};
+    ///
+    /// use miette::{JSONReportHandler, MietteDiagnostic, ReportHandler};
+    ///
+    /// let mut stdout = BufWriter::new(io::stdout().lock());
+    /// let diagnostic = MietteDiagnostic::new("unexpected token");
+    /// JSONReportHandler::new().render_report_io(&mut stdout, &diagnostic)?;
+    /// stdout.flush()?;
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    fn render_report_io(
+        &self,
+        w: &mut dyn io::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> io::Result<()> {
+        render_io(w, |f| self.render_report(f, diagnostic))
+    }
+
+    /// Render a batch of diagnostics straight into `w`, like
+    /// [`ReportHandler::render_reports`]. `w` is not flushed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first write to `w` that fails, or an error
+    /// of kind [`io::ErrorKind::InvalidData`] when rendering fails for
+    /// another reason.
+    fn render_reports_io(
+        &self,
+        w: &mut dyn io::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> io::Result<()> {
+        render_io(w, |f| self.render_reports(f, diagnostics))
+    }
+}
+
+/// Forwards formatted output to an [`io::Write`], keeping the error that
+/// [`fmt::Write`] has no room for.
+struct IoWriter<'a> {
+    inner: &'a mut dyn io::Write,
+    error: Option<io::Error>,
+}
+
+impl fmt::Write for IoWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+/// Runs `render` against `w`, returning the IO error behind a failed render.
+fn render_io(
+    w: &mut dyn io::Write,
+    render: impl FnOnce(&mut dyn fmt::Write) -> fmt::Result,
+) -> io::Result<()> {
+    let mut writer = IoWriter { inner: w, error: None };
+    render(&mut writer).map_err(|fmt::Error| {
+        // Renderers also fail without writing, such as the graphical renderer
+        // when a label is outside its source, and those errors are about
+        // the diagnostic rather than `w`.
+        writer.error.take().unwrap_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "failed to render a report")
+        })
+    })
+}
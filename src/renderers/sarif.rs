@@ -0,0 +1,297 @@
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+};
+
+use rustc_hash::FxHashMap;
+
+use super::{
+    json::escape,
+    location::{Location, Locator, flatten},
+};
+use crate::{
+    ColumnUnit, LabeledSpan, Severity, SourceCode, protocol::Diagnostic, renderers::ReportHandler,
+    source::remap_labels,
+};
+
+/**
+Renders batches of diagnostics as a [SARIF 2.1.0] log, the format GitHub
+code scanning and other static analysis dashboards ingest.
+
+Each diagnostic, and each of its related diagnostics, becomes a result:
+
+- [`Diagnostic::code`] is the `ruleId`, and the run's rules carry the
+  [`Diagnostic::help`] and [`Diagnostic::url`] of the first diagnostic with
+  that code that has them.
+- [`Diagnostic::severity`] is the `level`: `error`, `warning` or `note`.
+- The primary label, or the first label if none is primary, is the
+  result's location, and the other labels are its related locations. Each
+  has a region with 1-based lines and columns, its byte offset and length,
+  and the labeled text as its snippet. Labels into a source without a
+  [`SourceCode::name`] are left out, as SARIF locates them by file.
+
+Columns count UTF-16 code units, SARIF's default `columnKind`. File names
+are written as relative URIs, with `\` turned into `/` and other reserved
+characters percent-encoded.
+
+# Examples
+```
+use std::sync::Arc;
+
+use miette::{LabeledSpan, MietteDiagnostic, NamedSource, SarifReportHandler};
+
+let source = Arc::new(NamedSource::new("src/main.js", String::from("var x = 1;\n")));
+let diagnostic = MietteDiagnostic::new("unexpected `var`")
+    .with_code("no-var")
+    .with_help("use `let` or `const` instead")
+    .with_label(LabeledSpan::at(0..3, "declared here"))
+    .with_source_code(source);
+
+let mut log = String::new();
+SarifReportHandler::new("my-linter")
+    .with_tool_version("1.2.0")
+    .render_reports(&mut log, &[&diagnostic])
+    .unwrap();
+assert!(log.contains(r#""ruleId": "no-var""#));
+```
+
+[SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+*/
+#[derive(Debug, Clone)]
+pub struct SarifReportHandler {
+    tool_name: String,
+    tool_version: Option<String>,
+}
+
+impl SarifReportHandler {
+    /// Create a new [`SarifReportHandler`] for a tool, the `tool.driver` of
+    /// the log, called `tool_name`.
+    #[must_use]
+    pub fn new(tool_name: impl Into<String>) -> Self {
+        Self { tool_name: tool_name.into(), tool_version: None }
+    }
+
+    /// Set the version of the tool.
+    #[must_use]
+    pub fn with_tool_version(mut self, version: impl Into<String>) -> Self {
+        self.tool_version = Some(version.into());
+        self
+    }
+
+    /// Render a log with a single [`Diagnostic`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered log fails.
+    pub fn render_report(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        self.render_reports(f, &[diagnostic])
+    }
+
+    /// Render a log with one run holding every diagnostic of the batch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing the rendered log fails.
+    pub fn render_reports(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> fmt::Result {
+        let mut results = Vec::new();
+        for &diagnostic in diagnostics {
+            flatten(&mut results, diagnostic, None);
+        }
+        let mut rules = Vec::<Rule<'_>>::new();
+        let mut rule_indices = FxHashMap::<Cow<'_, str>, usize>::default();
+        for (diagnostic, _) in &results {
+            let Some(code) = diagnostic.code() else { continue };
+            let index = *rule_indices.entry(code).or_insert_with_key(|code| {
+                rules.push(Rule { id: code.clone(), help: None, url: None });
+                rules.len() - 1
+            });
+            let rule = &mut rules[index];
+            rule.help = rule.help.take().or_else(|| diagnostic.help());
+            rule.url = rule.url.take().or_else(|| diagnostic.url());
+        }
+
+        write!(f, r#"{{"$schema": "https://json.schemastore.org/sarif-2.1.0.json","#)?;
+        write!(f, r#""version": "2.1.0","#)?;
+        write!(f, r#""runs": [{{"tool": {{"driver": {{"#)?;
+        write!(f, r#""name": "{}","#, escape(&self.tool_name))?;
+        if let Some(version) = &self.tool_version {
+            write!(f, r#""version": "{}","#, escape(version))?;
+        }
+        write!(f, r#""rules": ["#)?;
+        for (i, rule) in rules.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, r#"{{"id": "{}""#, escape(&rule.id))?;
+            if let Some(help) = &rule.help {
+                write!(f, r#","help": {{"text": "{}"}}"#, escape(help))?;
+            }
+            if let Some(url) = &rule.url {
+                write!(f, r#","helpUri": "{}""#, escape(url))?;
+            }
+            write!(f, "}}")?;
+        }
+        write!(f, "]}}}},")?;
+        write!(f, r#""columnKind": "utf16CodeUnits","#)?;
+        write!(f, r#""results": ["#)?;
+        let mut locator = Locator::default();
+        for (i, &(diagnostic, source)) in results.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            let rule_index = diagnostic.code().map(|code| rule_indices[&code]);
+            Self::render_result(f, &mut locator, diagnostic, source, rule_index)?;
+        }
+        write!(f, "]}}]}}")
+    }
+
+    /// Renders one result object. `source` is the diagnostic's source code,
+    /// or that of the diagnostic it is related to.
+    fn render_result<'a>(
+        f: &mut impl fmt::Write,
+        locator: &mut Locator<'a>,
+        diagnostic: &'a dyn Diagnostic,
+        source: Option<&'a dyn SourceCode>,
+        rule_index: Option<usize>,
+    ) -> fmt::Result {
+        write!(f, "{{")?;
+        if let (Some(code), Some(index)) = (diagnostic.code(), rule_index) {
+            write!(f, r#""ruleId": "{}","ruleIndex": {index},"#, escape(&code))?;
+        }
+        let level = match diagnostic.severity() {
+            Some(Severity::Error) | None => "error",
+            Some(Severity::Warning) => "warning",
+            Some(Severity::Advice) => "note",
+        };
+        write!(f, r#""level": "{level}","#)?;
+        write!(f, r#""message": {{"text": "{}"}}"#, escape(&diagnostic.to_string()))?;
+        // Remapped labels only live until the end of this result, so they
+        // cannot share the batch's scanners.
+        match remap_labels(diagnostic.labels(), source) {
+            Cow::Borrowed(labels) => Self::render_locations(f, locator, labels, source)?,
+            Cow::Owned(labels) => {
+                Self::render_locations(f, &mut Locator::default(), &labels, source)?;
+            }
+        }
+        write!(f, "}}")
+    }
+
+    /// Renders the `locations` and `relatedLocations` members of a result.
+    fn render_locations<'a>(
+        f: &mut impl fmt::Write,
+        locator: &mut Locator<'a>,
+        labels: &'a [LabeledSpan],
+        source: Option<&'a dyn SourceCode>,
+    ) -> fmt::Result {
+        let located = labels.iter().filter_map(|label| {
+            let source = label.source_code().or(source)?;
+            let name = source.name()?;
+            Some((label, source, name))
+        });
+        let primary = located.clone().find(|(label, ..)| label.primary());
+        let Some(primary) = primary.or_else(|| located.clone().next()) else {
+            return Ok(());
+        };
+        write!(f, r#","locations": [{{"#)?;
+        Self::render_location(f, locator, primary)?;
+        write!(f, "}}]")?;
+        let mut related = located.filter(|(label, ..)| !std::ptr::eq(*label, primary.0)).peekable();
+        if related.peek().is_some() {
+            write!(f, r#","relatedLocations": ["#)?;
+            for (id, location) in related.enumerate() {
+                if id > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, r#"{{"id": {id},"#)?;
+                Self::render_location(f, locator, location)?;
+                write!(f, "}}")?;
+            }
+            write!(f, "]")?;
+        }
+        Ok(())
+    }
+
+    /// Renders the members of a location object, without its braces.
+    fn render_location<'a>(
+        f: &mut impl fmt::Write,
+        locator: &mut Locator<'a>,
+        (label, source, name): (&'a LabeledSpan, &'a dyn SourceCode, &str),
+    ) -> fmt::Result {
+        let span = *label.inner();
+        write!(f, r#""physicalLocation": {{"#)?;
+        write!(f, r#""artifactLocation": {{"uri": "{}"}},"#, Uri(name))?;
+        write!(f, r#""region": {{"#)?;
+        if let Some(Location { start, end }) = locator.locate(source, span, ColumnUnit::Utf16) {
+            write!(f, r#""startLine": {},"startColumn": {},"#, start.line + 1, start.column + 1)?;
+            write!(f, r#""endLine": {},"endColumn": {},"#, end.line + 1, end.column + 1)?;
+        }
+        let offset = u64::from(source.origin().offset) + u64::from(span.offset());
+        write!(f, r#""byteOffset": {offset},"byteLength": {}"#, span.len())?;
+        let snippet = source.is_available().then(|| {
+            let start = span.offset() as usize;
+            source.data().get(start..start + span.len() as usize)
+        });
+        if let Some(text) = snippet.flatten() {
+            let text = String::from_utf8_lossy(text);
+            write!(f, r#","snippet": {{"text": "{}"}}"#, escape(&text))?;
+        }
+        write!(f, "}}}}")?;
+        if let Some(text) = label.label() {
+            write!(f, r#","message": {{"text": "{}"}}"#, escape(text))?;
+        }
+        Ok(())
+    }
+}
+
+/// A rule of the run, one per distinct code.
+struct Rule<'a> {
+    id: Cow<'a, str>,
+    help: Option<Cow<'a, str>>,
+    url: Option<Cow<'a, str>>,
+}
+
+/// A file name as a relative URI reference.
+struct Uri<'a>(&'a str);
+
+impl fmt::Display for Uri<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &byte in self.0.as_bytes() {
+            match byte {
+                b'\\' => f.write_char('/')?,
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                    f.write_char(char::from(byte))?;
+                }
+                _ => write!(f, "%{byte:02X}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Batches are rendered as one log, and a single report as a log of its
+/// own.
+impl ReportHandler for SarifReportHandler {
+    fn render_report(
+        &self,
+        mut f: &mut dyn fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        Self::render_report(self, &mut f, diagnostic)
+    }
+
+    fn render_reports(
+        &self,
+        mut f: &mut dyn fmt::Write,
+        diagnostics: &[&dyn Diagnostic],
+    ) -> fmt::Result {
+        Self::render_reports(self, &mut f, diagnostics)
+    }
+}
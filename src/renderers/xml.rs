@@ -0,0 +1,42 @@
+//! Escaping for the renderers that write XML.
+
+use std::fmt::{self, Write};
+
+/// Escapes text for an XML attribute value or element content.
+///
+/// Line breaks and tabs become character references so attribute values
+/// keep them, and other control characters, which XML 1.0 cannot hold, are
+/// replaced with U+FFFD.
+pub(super) struct Escape<'a>(&'a str);
+
+impl fmt::Display for Escape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&apos;")?,
+                '\n' => f.write_str("&#10;")?,
+                '\r' => f.write_str("&#13;")?,
+                '\t' => f.write_str("&#9;")?,
+                c if c < ' ' || matches!(c, '\u{fffe}' | '\u{ffff}') => {
+                    f.write_char(char::REPLACEMENT_CHARACTER)?;
+                }
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(super) const fn escape(input: &'_ str) -> Escape<'_> {
+    Escape(input)
+}
+
+#[test]
+fn test_escape() {
+    assert_eq!(escape(r#"<a href="x">&'"#).to_string(), "&lt;a href=&quot;x&quot;&gt;&amp;&apos;");
+    assert_eq!(escape("a\r\n\tb\u{1b}c\u{ffff}").to_string(), "a&#13;&#10;&#9;b\u{fffd}c\u{fffd}");
+}
@@ -0,0 +1,87 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use crate::SourceCode;
+
+/// [`SourceCode`] read from a file the first time its contents are needed,
+/// named after its path.
+///
+/// Creating one does not touch the file system, so a batch of diagnostics
+/// over many files only reads the files that are actually rendered. If the
+/// file cannot be read, [`data`](SourceCode::data) is empty,
+/// [`is_available`](SourceCode::is_available) is `false` and renderers
+/// leave out its snippets.
+///
+/// # Examples
+/// ```
+/// use miette::{FileSource, SourceCode};
+///
+/// let source = FileSource::new("does/not/exist.js");
+/// assert_eq!(source.name(), Some("does/not/exist.js"));
+/// assert!(!source.is_available());
+/// assert!(source.data().is_empty());
+/// assert!(source.error().is_some());
+/// ```
+pub struct FileSource {
+    path: PathBuf,
+    name: String,
+    contents: OnceLock<io::Result<Box<[u8]>>>,
+}
+
+impl FileSource {
+    /// Creates a source for the file at `path` without reading it.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let name = path.to_string_lossy().into_owned();
+        Self { path, name, contents: OnceLock::new() }
+    }
+
+    /// The path of the file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The error reading the file failed with, reading it first if needed.
+    #[must_use]
+    pub fn error(&self) -> Option<&io::Error> {
+        self.contents().as_ref().err()
+    }
+
+    fn contents(&self) -> &io::Result<Box<[u8]>> {
+        self.contents.get_or_init(|| fs::read(&self.path).map(Vec::into_boxed_slice))
+    }
+}
+
+impl fmt::Debug for FileSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self.contents.get() {
+            None => "unread",
+            Some(Ok(_)) => "read",
+            Some(Err(_)) => "failed",
+        };
+        f.debug_struct("FileSource")
+            .field("path", &self.path)
+            .field("name", &self.name)
+            .field("contents", &state)
+            .finish()
+    }
+}
+
+impl SourceCode for FileSource {
+    fn data(&self) -> &[u8] {
+        self.contents().as_deref().unwrap_or_default()
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn is_available(&self) -> bool {
+        self.contents().is_ok()
+    }
+}
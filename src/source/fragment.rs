@@ -0,0 +1,100 @@
+use super::index::LineColumn;
+use crate::{LabeledSpan, SourceCode};
+
+/// Where a fragment starts inside the file it was taken from.
+///
+/// `line` and `column` are 0-based, and `column` counts bytes like the
+/// columns the renderers report.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Default)]
+pub struct SourceOrigin {
+    /// The byte offset of the fragment in the file.
+    pub offset: u32,
+    /// The line of the file the fragment starts on.
+    pub line: usize,
+    /// The column of that line the fragment starts at.
+    pub column: usize,
+}
+
+impl SourceOrigin {
+    /// Makes an origin from a byte offset and the 0-based line and column
+    /// it is at.
+    #[must_use]
+    pub const fn new(offset: u32, line: usize, column: usize) -> Self {
+        Self { offset, line, column }
+    }
+
+    /// Translates a position in the fragment to the same position in the
+    /// file. Only the fragment's first line is shifted right.
+    #[must_use]
+    pub const fn to_parent(self, position: LineColumn) -> LineColumn {
+        if position.line == 0 {
+            LineColumn::new(self.line, self.column + position.column)
+        } else {
+            LineColumn::new(self.line + position.line, position.column)
+        }
+    }
+}
+
+/// [`SourceCode`] for a fragment of a larger file, such as a `<script>`
+/// block in a Vue, Svelte, Astro or HTML file.
+///
+/// Spans point into the fragment, but renderers report line numbers,
+/// columns and offsets in the enclosing file, so a fragment parsed on its own
+/// does not need its labels shifted by hand.
+///
+/// # Examples
+/// ```
+/// use miette::{LineColumn, SourceCode, SourceFragment, SourceOrigin};
+///
+/// let file = "<template></template>\n<script>\nlet a = 1;\n</script>\n";
+/// let start = file.find("\nlet").unwrap() + 1;
+/// let fragment = &file[start..file.find("</script>").unwrap()];
+/// let source = SourceFragment::new(fragment, SourceOrigin::new(start as u32, 2, 0));
+/// assert_eq!(source.data(), b"let a = 1;\n");
+/// assert_eq!(source.origin().to_parent(LineColumn::new(0, 4)), LineColumn::new(2, 4));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SourceFragment<S> {
+    source: S,
+    origin: SourceOrigin,
+}
+
+impl<S: SourceCode> SourceFragment<S> {
+    /// Wraps `source`, the text of a fragment starting at `origin`.
+    #[must_use]
+    pub const fn new(source: S, origin: SourceOrigin) -> Self {
+        Self { source, origin }
+    }
+
+    /// The wrapped fragment text.
+    #[must_use]
+    pub const fn inner(&self) -> &S {
+        &self.source
+    }
+}
+
+impl<S: SourceCode> SourceCode for SourceFragment<S> {
+    fn data(&self) -> &[u8] {
+        self.source.data()
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.source.name()
+    }
+
+    fn is_available(&self) -> bool {
+        self.source.is_available()
+    }
+
+    fn remap_label(&self, label: &LabeledSpan) -> Option<LabeledSpan> {
+        self.source.remap_label(label)
+    }
+
+    fn origin(&self) -> SourceOrigin {
+        self.origin
+    }
+
+    fn line_starts(&self) -> Option<&[usize]> {
+        self.source.line_starts()
+    }
+}
@@ -0,0 +1,281 @@
+//! Conversion between byte offsets and line/column positions.
+
+use std::{borrow::Cow, ops::ControlFlow};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::reader::{LineBreak, LineBreaks};
+use crate::{SourceCode, SourceSpan};
+
+/// What a column counts.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Default)]
+pub enum ColumnUnit {
+    /// Bytes, as in [`SourceSpan`] offsets.
+    #[default]
+    Byte,
+    /// Unicode scalar values (`char`s).
+    Char,
+    /// UTF-16 code units, as in LSP positions.
+    Utf16,
+    /// Extended grapheme clusters, as a user would count characters.
+    Grapheme,
+}
+
+/// A 0-based line and column in some [`ColumnUnit`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub struct LineColumn {
+    /// The 0-based line number.
+    pub line: usize,
+    /// The 0-based column within the line.
+    pub column: usize,
+}
+
+impl LineColumn {
+    /// Makes a position from a 0-based line and column.
+    #[must_use]
+    pub const fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+/// Index of line starts over a [`SourceCode`], mapping byte offsets to
+/// line/column positions and back.
+///
+/// Lines end at `\n`, `\r\n` or a lone `\r`, as in the renderers. Invalid
+/// UTF-8 counts one column per byte in every unit.
+///
+/// # Examples
+/// ```
+/// use miette::{ColumnUnit, LineColumn, SourceIndex};
+///
+/// let source = "let a = 1;\nlet 🦀 = 2;";
+/// let index = SourceIndex::new(&source);
+/// let crab = 15;
+/// assert_eq!(index.line_column(crab, ColumnUnit::Byte), Some(LineColumn::new(1, 4)));
+/// assert_eq!(index.line_column(crab + 4, ColumnUnit::Byte), Some(LineColumn::new(1, 8)));
+/// assert_eq!(index.line_column(crab + 4, ColumnUnit::Char), Some(LineColumn::new(1, 5)));
+/// assert_eq!(index.line_column(crab + 4, ColumnUnit::Utf16), Some(LineColumn::new(1, 6)));
+/// assert_eq!(index.offset(LineColumn::new(1, 6), ColumnUnit::Utf16), Some(crab + 4));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SourceIndex<'a> {
+    data: &'a [u8],
+    line_starts: Cow<'a, [usize]>,
+}
+
+impl<'a> SourceIndex<'a> {
+    /// Indexes the lines of `source`, borrowing its
+    /// [`SourceCode::line_starts`] when it has them.
+    #[must_use]
+    pub fn new<S: SourceCode + ?Sized>(source: &'a S) -> Self {
+        let data = source.data();
+        if let Some(line_starts) = checked_line_starts(source) {
+            return Self::from_line_starts(data, line_starts);
+        }
+        let mut line_starts = vec![0];
+        line_starts.extend(LineBreaks::new(data).map(LineBreak::next_line_start));
+        Self { data, line_starts: Cow::Owned(line_starts) }
+    }
+
+    /// An index over `data` with line starts computed earlier by
+    /// [`SourceIndex::into_line_starts`].
+    pub(super) fn from_line_starts(data: &'a [u8], line_starts: &'a [usize]) -> Self {
+        Self { data, line_starts: Cow::Borrowed(line_starts) }
+    }
+
+    /// The line starts, to keep for [`SourceIndex::from_line_starts`].
+    pub(super) fn into_line_starts(self) -> Vec<usize> {
+        self.line_starts.into_owned()
+    }
+
+    /// The byte offset every line starts at, in the form
+    /// [`SourceCode::line_starts`] expects.
+    #[must_use]
+    pub fn line_starts(&self) -> &[usize] {
+        &self.line_starts
+    }
+
+    /// The number of lines. An empty source, or one ending in a line break,
+    /// has an empty last line.
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The byte offset where `line` starts.
+    #[must_use]
+    pub fn line_start(&self, line: usize) -> Option<u32> {
+        self.line_starts.get(line).and_then(|&start| u32::try_from(start).ok())
+    }
+
+    /// The bytes of `line`, without its line break.
+    fn line_bytes(&self, line: usize) -> &'a [u8] {
+        let start = self.line_starts[line];
+        let end = self.line_starts.get(line + 1).map_or(self.data.len(), |&next| {
+            if next >= 2 && self.data[next - 2] == b'\r' && self.data[next - 1] == b'\n' {
+                next - 2
+            } else {
+                next - 1
+            }
+        });
+        &self.data[start..end]
+    }
+
+    /// The position of byte `offset`, or `None` past the end of the source.
+    ///
+    /// An offset inside a character, or inside a line break, is reported at
+    /// the column where that character or line break starts.
+    #[must_use]
+    pub fn line_column(&self, offset: u32, unit: ColumnUnit) -> Option<LineColumn> {
+        let offset = offset as usize;
+        if offset > self.data.len() {
+            return None;
+        }
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let bytes = self.line_bytes(line);
+        let target = (offset - self.line_starts[line]).min(bytes.len());
+        if unit == ColumnUnit::Byte {
+            return Some(LineColumn { line, column: target });
+        }
+        let mut column = 0;
+        for_each_unit(bytes, unit, |start, width| {
+            if start + width > target {
+                return ControlFlow::Break(());
+            }
+            column += unit_columns(unit, width);
+            ControlFlow::Continue(())
+        });
+        Some(LineColumn { line, column })
+    }
+
+    /// The byte offset of `position`, or `None` if its line does not exist,
+    /// its column is past the end of the line, or it points inside a
+    /// character (such as between the two halves of a UTF-16 surrogate pair).
+    #[must_use]
+    pub fn offset(&self, position: LineColumn, unit: ColumnUnit) -> Option<u32> {
+        let line_start = *self.line_starts.get(position.line)?;
+        let bytes = self.line_bytes(position.line);
+        if unit == ColumnUnit::Byte {
+            if position.column > bytes.len() {
+                return None;
+            }
+            return u32::try_from(line_start + position.column).ok();
+        }
+        let mut column = 0;
+        let mut found = None;
+        for_each_unit(bytes, unit, |start, width| {
+            if column >= position.column {
+                if column == position.column {
+                    found = Some(start);
+                }
+                return ControlFlow::Break(());
+            }
+            column += unit_columns(unit, width);
+            ControlFlow::Continue(())
+        });
+        let byte = match found {
+            Some(byte) => byte,
+            None if column == position.column => bytes.len(),
+            None => return None,
+        };
+        u32::try_from(line_start + byte).ok()
+    }
+
+    /// The span from `start` up to `end`, or `None` if either position is
+    /// invalid or `end` comes before `start`.
+    #[must_use]
+    pub fn span(&self, start: LineColumn, end: LineColumn, unit: ColumnUnit) -> Option<SourceSpan> {
+        let start = self.offset(start, unit)?;
+        let end = self.offset(end, unit)?;
+        (start <= end).then(|| SourceSpan::from(start..end))
+    }
+}
+
+/// The number of `unit` columns in `line`, the start of a line up to some
+/// offset.
+pub fn column_count(line: &[u8], unit: ColumnUnit) -> usize {
+    if unit == ColumnUnit::Byte {
+        return line.len();
+    }
+    let mut column = 0;
+    for_each_unit(line, unit, |_, width| {
+        column += unit_columns(unit, width);
+        ControlFlow::Continue(())
+    });
+    column
+}
+
+/// The columns a unit of `width` bytes takes up: characters outside the
+/// Basic Multilingual Plane are two UTF-16 code units, everything else one.
+const fn unit_columns(unit: ColumnUnit, width: usize) -> usize {
+    if matches!(unit, ColumnUnit::Utf16) && width == 4 { 2 } else { 1 }
+}
+
+/// Calls `f` with the byte start and byte width of each character (or
+/// grapheme) of `line`, in order, until it breaks.
+fn for_each_unit(
+    line: &[u8],
+    unit: ColumnUnit,
+    mut f: impl FnMut(usize, usize) -> ControlFlow<()>,
+) {
+    let mut chunk_start = 0;
+    for chunk in line.utf8_chunks() {
+        let valid = chunk.valid();
+        let stopped = if unit == ColumnUnit::Grapheme {
+            valid.grapheme_indices(true).try_for_each(|(start, g)| f(chunk_start + start, g.len()))
+        } else {
+            valid.char_indices().try_for_each(|(start, c)| f(chunk_start + start, c.len_utf8()))
+        };
+        if stopped.is_break() {
+            return;
+        }
+        chunk_start += valid.len();
+        for _ in chunk.invalid() {
+            if f(chunk_start, 1).is_break() {
+                return;
+            }
+            chunk_start += 1;
+        }
+    }
+}
+
+/// The [`SourceCode::line_starts`] of `source`, unless the table does not
+/// start at `0`, is not strictly increasing, or has a start that is not
+/// right after a line break. This looks at one byte per line rather than
+/// scanning the source, so a table that leaves a line out still passes.
+pub(super) fn checked_line_starts<S: SourceCode + ?Sized>(source: &S) -> Option<&[usize]> {
+    let line_starts = source.line_starts()?;
+    let data = source.data();
+    let follows_break = |start: usize| match data.get(start.wrapping_sub(1)) {
+        Some(b'\n') => true,
+        Some(b'\r') => data.get(start) != Some(&b'\n'),
+        _ => false,
+    };
+    let valid = line_starts.first() == Some(&0)
+        && line_starts.windows(2).all(|pair| pair[0] < pair[1] && follows_break(pair[1]));
+    valid.then_some(line_starts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::reader::SpanScanner;
+
+    /// Byte positions must agree with the renderers' span reader for every
+    /// offset, across all line break styles.
+    #[test]
+    fn byte_columns_match_span_reader() {
+        let sources = ["", "a", "\n", "ab\ncd", "a\r\nb\rc\n\r\n", "é\r🦀\n\nx", "\r\r\n\n"];
+        for source in sources {
+            let index = SourceIndex::new(source);
+            for offset in 0..=u32::try_from(source.len()).unwrap() {
+                let contents = SpanScanner::new(source.as_bytes(), 0, 0)
+                    .read_span((offset, 0).into())
+                    .expect("offset is in bounds");
+                let expected = LineColumn::new(contents.line(), contents.column());
+                let got = index.line_column(offset, ColumnUnit::Byte);
+                assert_eq!(got, Some(expected), "source={source:?} offset={offset}");
+            }
+        }
+    }
+}
@@ -1,13 +1,21 @@
 //! Source-code adapters and span reading.
 
-use std::sync::Arc;
+use std::{borrow::Cow, ptr, sync::Arc};
 
-use crate::SourceCode;
+use crate::{LabeledSpan, SourceCode};
 
+pub use file::FileSource;
+pub use fragment::{SourceFragment, SourceOrigin};
+pub use index::{ColumnUnit, LineColumn, SourceIndex, column_count};
 pub use named::NamedSource;
+pub use source_map::{MappedSource, SourceMapError};
 
+mod file;
+mod fragment;
+mod index;
 mod named;
 pub mod reader;
+mod source_map;
 
 impl SourceCode for str {
     fn data(&self) -> &[u8] {
@@ -36,4 +44,45 @@ impl<T: ?Sized + SourceCode> SourceCode for Arc<T> {
     fn name(&self) -> Option<&str> {
         self.as_ref().name()
     }
+
+    fn is_available(&self) -> bool {
+        self.as_ref().is_available()
+    }
+
+    fn remap_label(&self, label: &LabeledSpan) -> Option<LabeledSpan> {
+        self.as_ref().remap_label(label)
+    }
+
+    fn origin(&self) -> SourceOrigin {
+        self.as_ref().origin()
+    }
+
+    fn line_starts(&self) -> Option<&[usize]> {
+        self.as_ref().line_starts()
+    }
+}
+
+/// Whether two sources are the same: the same object, or equal names and
+/// contents. Used by the `PartialEq` impls of types holding shared sources.
+pub fn same_source(a: &dyn SourceCode, b: &dyn SourceCode) -> bool {
+    ptr::addr_eq(a, b) || (a.name() == b.name() && a.data() == b.data())
+}
+
+/// `labels` as renderers should show them: each label without a source of
+/// its own passed through `source`'s [`SourceCode::remap_label`]. Borrows
+/// `labels` when nothing is remapped.
+pub fn remap_labels<'a>(
+    labels: &'a [LabeledSpan],
+    source: Option<&dyn SourceCode>,
+) -> Cow<'a, [LabeledSpan]> {
+    let mut labels = Cow::Borrowed(labels);
+    let Some(source) = source else { return labels };
+    for i in 0..labels.len() {
+        if labels[i].source_code().is_none() {
+            if let Some(remapped) = source.remap_label(&labels[i]) {
+                labels.to_mut()[i] = remapped;
+            }
+        }
+    }
+    labels
 }
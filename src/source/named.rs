@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::SourceCode;
+use crate::{LabeledSpan, SourceCode, SourceOrigin};
 
 /// Utility struct for when you have a regular [`SourceCode`] type that doesn't
 /// implement `name`. For example [`String`]. Or if you want to override the
@@ -38,4 +38,20 @@ impl<S: SourceCode + 'static> SourceCode for NamedSource<S> {
     fn name(&self) -> Option<&str> {
         Some(&self.name)
     }
+
+    fn is_available(&self) -> bool {
+        self.source.is_available()
+    }
+
+    fn remap_label(&self, label: &LabeledSpan) -> Option<LabeledSpan> {
+        self.source.remap_label(label)
+    }
+
+    fn origin(&self) -> SourceOrigin {
+        self.source.origin()
+    }
+
+    fn line_starts(&self) -> Option<&[usize]> {
+        self.source.line_starts()
+    }
 }
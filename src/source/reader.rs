@@ -1,10 +1,11 @@
 //! Source span reading and line indexing.
 
-use std::collections::VecDeque;
 #[cfg(test)]
 use std::str::from_utf8;
+use std::{borrow::Cow, collections::VecDeque};
 
-use crate::SourceSpan;
+use super::index::checked_line_starts;
+use crate::{SourceCode, SourceSpan};
 
 #[derive(Debug)]
 pub struct SpanContents<'a> {
@@ -95,7 +96,7 @@ impl SpanRequest {
 /// A logical line break. `start == end` for LF or CR and differs by one for
 /// CRLF, allowing all scanners to share the same newline handling.
 #[derive(Clone, Copy)]
-struct LineBreak {
+pub(super) struct LineBreak {
     start: usize,
     end: usize,
 }
@@ -107,7 +108,7 @@ impl LineBreak {
         Self { start, end }
     }
 
-    const fn next_line_start(self) -> usize {
+    pub(super) const fn next_line_start(self) -> usize {
         self.end + 1
     }
 
@@ -118,13 +119,13 @@ impl LineBreak {
 
 /// Iterator over logical line breaks in a slice. It consumes CRLF as one item,
 /// so callers do not need their own "skip the LF" branches.
-struct LineBreaks<'a> {
+pub(super) struct LineBreaks<'a> {
     input: &'a [u8],
     positions: memchr::Memchr2<'a>,
 }
 
 impl<'a> LineBreaks<'a> {
-    fn new(input: &'a [u8]) -> Self {
+    pub(super) fn new(input: &'a [u8]) -> Self {
         Self { input, positions: memchr::memchr2_iter(b'\r', b'\n', input) }
     }
 }
@@ -481,8 +482,9 @@ struct LineIndex<'a> {
     input: &'a [u8],
     /// Starts (byte offsets) of consecutive lines, the first of which is line
     /// number `base_line`; covers every line whose start lies in
-    /// `[line_starts[0], frontier]`. Empty until the first query seeds it.
-    line_starts: Vec<usize>,
+    /// `[line_starts[0], frontier]`. Empty until the first query seeds it,
+    /// unless borrowed complete from [`SourceCode::line_starts`].
+    line_starts: Cow<'a, [usize]>,
     /// 0-indexed line number of `line_starts[0]`.
     base_line: usize,
     /// Bytes in `[0, frontier)` have been scanned: every line break there is
@@ -493,7 +495,13 @@ struct LineIndex<'a> {
 
 impl<'a> LineIndex<'a> {
     fn new(input: &'a [u8]) -> Self {
-        Self { input, line_starts: Vec::new(), base_line: 0, frontier: 0 }
+        Self { input, line_starts: Cow::Borrowed(&[]), base_line: 0, frontier: 0 }
+    }
+
+    /// An index that already covers all of `input`, from the start of every
+    /// line.
+    const fn complete(input: &'a [u8], line_starts: &'a [usize]) -> Self {
+        Self { input, line_starts: Cow::Borrowed(line_starts), base_line: 0, frontier: input.len() }
     }
 
     fn is_empty(&self) -> bool {
@@ -511,9 +519,10 @@ impl<'a> LineIndex<'a> {
             PrefixScan::new(self.input, cut, context_lines_before);
         debug_assert_eq!(leading.start_line + leading.len(), line_count);
         self.base_line = leading.start_line;
-        self.line_starts.reserve(leading.len() + 8);
-        leading.append_to(&mut self.line_starts);
-        self.line_starts.push(current_line_start);
+        let line_starts = self.line_starts.to_mut();
+        line_starts.reserve(leading.len() + 8);
+        leading.append_to(line_starts);
+        line_starts.push(current_line_start);
         self.frontier = cut;
     }
 
@@ -531,7 +540,7 @@ impl<'a> LineIndex<'a> {
             return;
         }
         for line_break in LineBreaks::new(&self.input[self.frontier..target]) {
-            self.line_starts.push(line_break.shifted(self.frontier).next_line_start());
+            self.line_starts.to_mut().push(line_break.shifted(self.frontier).next_line_start());
         }
         self.frontier = target;
     }
@@ -542,7 +551,7 @@ impl<'a> LineIndex<'a> {
     fn extend(&mut self) -> Option<LineBreak> {
         if let Some(line_break) = LineBreaks::new(&self.input[self.frontier..]).next() {
             let line_break = line_break.shifted(self.frontier);
-            self.line_starts.push(line_break.next_line_start());
+            self.line_starts.to_mut().push(line_break.next_line_start());
             self.frontier = line_break.next_line_start();
             Some(line_break)
         } else {
@@ -738,6 +747,20 @@ impl<'a> SpanScanner<'a> {
         }
     }
 
+    /// A scanner over `source` that looks lines up in its
+    /// [`SourceCode::line_starts`] when it has them, and scans otherwise.
+    pub(crate) fn for_source<S: SourceCode + ?Sized>(
+        source: &'a S,
+        context_lines_before: usize,
+        context_lines_after: usize,
+    ) -> Self {
+        let mut scanner = Self::new(source.data(), context_lines_before, context_lines_after);
+        if let Some(line_starts) = checked_line_starts(source) {
+            scanner.index = LineIndex::complete(scanner.index.input, line_starts);
+        }
+        scanner
+    }
+
     /// Read a span while scanning only source bytes no earlier query scanned.
     pub(crate) fn read_span(&mut self, span: SourceSpan) -> Option<SpanContents<'a>> {
         let request = SpanRequest::new(span);
@@ -977,6 +1000,7 @@ mod scanner_tests {
     )]
 
     use super::*;
+    use crate::SourceIndex;
 
     /// Deterministic xorshift so any failure reproduces from a fixed seed.
     struct Rng(u64);
@@ -1047,6 +1071,28 @@ mod scanner_tests {
                   exercised under Miri by the normal snapshot tests"
     )]
     fn scanner_matches_span_reader_exhaustively() {
+        struct Indexed<'a> {
+            input: &'a [u8],
+            line_starts: Vec<usize>,
+        }
+
+        impl<'a> Indexed<'a> {
+            fn new(source: &'a str) -> Self {
+                let line_starts = SourceIndex::new(source).line_starts().to_vec();
+                Self { input: source.as_bytes(), line_starts }
+            }
+        }
+
+        impl SourceCode for Indexed<'_> {
+            fn data(&self) -> &[u8] {
+                self.input
+            }
+
+            fn line_starts(&self) -> Option<&[usize]> {
+                Some(&self.line_starts)
+            }
+        }
+
         let alphabets: &[&[&str]] = &[
             &["a", "\n"],
             &["a", "b", "c", "\n"],
@@ -1079,6 +1125,15 @@ mod scanner_tests {
                         checked += 1;
                     }
 
+                    // A source with precomputed line starts, in random order.
+                    let indexed = Indexed::new(&s);
+                    let mut scanner = SpanScanner::for_source(&indexed, before, after);
+                    assert!(!scanner.index.is_empty());
+                    for i in 0..spans.len() {
+                        check(&mut scanner, input, spans[i], before, after, &spans[..i]);
+                        checked += 1;
+                    }
+
                     // The renderer's order: labels sorted by offset, then a
                     // merge attempt spanning from the first label to the
                     // furthest end.
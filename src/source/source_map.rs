@@ -0,0 +1,416 @@
+use std::{error::Error, fmt, sync::Arc, sync::OnceLock};
+
+use super::index::{ColumnUnit, LineColumn, SourceIndex};
+use crate::{LabeledSpan, NamedSource, SourceCode, SourceOrigin, SourceSpan, json};
+
+/// [`SourceCode`] for generated code (compiler output, bundles) that reports
+/// labels against the original sources through a [source map].
+///
+/// Renderers call [`SourceCode::remap_label`] on every label that points
+/// into this source, so a diagnostic computed on the generated text is shown
+/// in the original file, at the original position. Originals come from the
+/// map's `sourcesContent`. A label that falls outside every mapping, or into
+/// a source without content, stays on the generated code, with its text
+/// noting that it could not be mapped. [`Diagnostic::suggestions`] are not
+/// remapped, and stay on the generated code.
+///
+/// [`Diagnostic::suggestions`]: crate::Diagnostic::suggestions
+/// [source map]: https://tc39.es/ecma426/
+///
+/// # Examples
+/// ```
+/// use miette::{LabeledSpan, MappedSource, SourceCode};
+///
+/// let generated = "var a = 1;\n";
+/// let map = r#"{
+///     "version": 3,
+///     "sources": ["a.ts"],
+///     "sourcesContent": ["let a: number = 1;\n"],
+///     "mappings": "AAAA,IAAI,CAAS"
+/// }"#;
+/// let source = MappedSource::new(generated, map).unwrap();
+/// let label = source.remap_label(&LabeledSpan::at(4..5, "here")).unwrap();
+/// assert_eq!(label.source_code().and_then(SourceCode::name), Some("a.ts"));
+/// assert_eq!((label.offset(), label.len()), (4, 1));
+/// ```
+pub struct MappedSource<S> {
+    generated: S,
+    unit: ColumnUnit,
+    originals: Vec<Option<Original>>,
+    lines: Vec<Vec<Segment>>,
+    generated_lines: OnceLock<Vec<usize>>,
+}
+
+/// An original source with content, indexed for position lookups.
+struct Original {
+    source: Arc<dyn SourceCode>,
+    line_starts: Vec<usize>,
+}
+
+/// One decoded segment of the `mappings` field.
+#[derive(Clone, Copy)]
+struct Segment {
+    generated_column: u32,
+    original: Option<Mapping>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Mapping {
+    source: u32,
+    line: u32,
+    column: u32,
+}
+
+/// Why a source map could not be loaded by [`MappedSource::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceMapError {
+    /// The source map is not valid JSON.
+    Json {
+        /// The byte offset of the error in the source map.
+        offset: usize,
+        /// What was wrong.
+        message: &'static str,
+    },
+    /// `version` is missing or is not 3.
+    UnsupportedVersion,
+    /// A field is missing or has the wrong type.
+    InvalidField(&'static str),
+    /// `mappings` holds a malformed segment, or one that refers to a source
+    /// that does not exist.
+    InvalidMappings {
+        /// The byte offset of the segment in `mappings`.
+        offset: usize,
+    },
+}
+
+impl fmt::Display for SourceMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json { offset, message } => {
+                write!(f, "source map is not valid JSON: {message} at byte {offset}")
+            }
+            Self::UnsupportedVersion => f.write_str("only version 3 source maps are supported"),
+            Self::InvalidField(field) => write!(f, "source map has an invalid `{field}` field"),
+            Self::InvalidMappings { offset } => {
+                write!(f, "source map has an invalid mapping at byte {offset} of `mappings`")
+            }
+        }
+    }
+}
+
+impl Error for SourceMapError {}
+
+impl<S: SourceCode> MappedSource<S> {
+    /// Wraps `generated` with the v3 source map `source_map`, given as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the source map is not valid JSON, is not a
+    /// version 3 map, or its `sources`, `sourcesContent` or `mappings` are
+    /// malformed. Index maps (with `sections`) are not supported.
+    pub fn new(generated: S, source_map: &str) -> Result<Self, SourceMapError> {
+        let map = json::parse(source_map).map_err(|error| SourceMapError::Json {
+            offset: error.offset,
+            message: error.message,
+        })?;
+        if map.get("version").and_then(json::Value::as_u64) != Some(3) {
+            return Err(SourceMapError::UnsupportedVersion);
+        }
+        let root = match map.get("sourceRoot") {
+            None | Some(json::Value::Null) => "",
+            Some(root) => root.as_str().ok_or(SourceMapError::InvalidField("sourceRoot"))?,
+        };
+        let sources = map
+            .get("sources")
+            .and_then(json::Value::as_array)
+            .ok_or(SourceMapError::InvalidField("sources"))?;
+        let contents = match map.get("sourcesContent") {
+            None | Some(json::Value::Null) => &[][..],
+            Some(contents) => {
+                contents.as_array().ok_or(SourceMapError::InvalidField("sourcesContent"))?
+            }
+        };
+        let originals = sources
+            .iter()
+            .enumerate()
+            .map(|(i, source)| {
+                let name = match source {
+                    json::Value::Null => "",
+                    source => source.as_str().ok_or(SourceMapError::InvalidField("sources"))?,
+                };
+                let content = match contents.get(i) {
+                    None | Some(json::Value::Null) => return Ok(None),
+                    Some(content) => {
+                        content.as_str().ok_or(SourceMapError::InvalidField("sourcesContent"))?
+                    }
+                };
+                let source = NamedSource::new(join_root(root, name), content.to_owned());
+                let line_starts = SourceIndex::new(&source).into_line_starts();
+                Ok(Some(Original { source: Arc::new(source), line_starts }))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mappings = map
+            .get("mappings")
+            .and_then(json::Value::as_str)
+            .ok_or(SourceMapError::InvalidField("mappings"))?;
+        let lines = decode_mappings(mappings, originals.len())?;
+        Ok(Self {
+            generated,
+            unit: ColumnUnit::Utf16,
+            originals,
+            lines,
+            generated_lines: OnceLock::new(),
+        })
+    }
+
+    /// Counts source map columns in `unit` instead of the UTF-16 code units
+    /// that JavaScript tools emit.
+    #[must_use]
+    pub fn with_column_unit(mut self, unit: ColumnUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// The generated source.
+    #[must_use]
+    pub const fn generated(&self) -> &S {
+        &self.generated
+    }
+
+    /// Maps `span` on the generated code to an original source.
+    fn map_span(&self, span: SourceSpan) -> Option<(&Arc<dyn SourceCode>, SourceSpan)> {
+        let line_starts = self
+            .generated_lines
+            .get_or_init(|| SourceIndex::new(&self.generated).into_line_starts());
+        let generated = SourceIndex::from_line_starts(self.generated.data(), line_starts);
+
+        let (mapping, delta) =
+            self.lookup(generated.line_column(span.offset(), self.unit)?, false)?;
+        let original = self.originals.get(mapping.source as usize)?.as_ref()?;
+        let data = original.source.data();
+        let index = SourceIndex::from_line_starts(data, &original.line_starts);
+        let start = self.original_offset(&index, mapping, delta)?;
+        let end = generated
+            .line_column(span.end(), self.unit)
+            .filter(|_| !span.is_empty())
+            .and_then(|end| self.lookup(end, true))
+            .filter(|(end, _)| end.source == mapping.source)
+            .and_then(|(end, delta)| self.original_offset(&index, end, delta))
+            .filter(|&end| end >= start)
+            .unwrap_or_else(|| {
+                let len = u32::try_from(data.len()).unwrap_or(u32::MAX);
+                start.saturating_add(span.len()).min(len)
+            });
+        Some((&original.source, SourceSpan::from(start..end)))
+    }
+
+    /// The mapping covering `position`, and how many columns past its start
+    /// the position is. With `exclusive`, a mapping starting exactly at
+    /// `position` does not cover it, as for the end of a span.
+    fn lookup(&self, position: LineColumn, exclusive: bool) -> Option<(Mapping, usize)> {
+        let segments = self.lines.get(position.line)?;
+        let column = u32::try_from(position.column).ok()?;
+        let after = segments.partition_point(|segment| {
+            if exclusive {
+                segment.generated_column < column
+            } else {
+                segment.generated_column <= column
+            }
+        });
+        let segment = segments[after.checked_sub(1)?];
+        Some((segment.original?, (column - segment.generated_column) as usize))
+    }
+
+    /// The byte offset `delta` columns past `mapping`, or of `mapping` itself
+    /// when that runs past the end of the original line.
+    fn original_offset(
+        &self,
+        index: &SourceIndex<'_>,
+        mapping: Mapping,
+        delta: usize,
+    ) -> Option<u32> {
+        let position = LineColumn::new(mapping.line as usize, mapping.column as usize);
+        index
+            .offset(LineColumn::new(position.line, position.column + delta), self.unit)
+            .or_else(|| index.offset(position, self.unit))
+    }
+}
+
+impl<S> fmt::Debug for MappedSource<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sources: Vec<_> = self
+            .originals
+            .iter()
+            .map(|original| original.as_ref().and_then(|original| original.source.name()))
+            .collect();
+        f.debug_struct("MappedSource")
+            .field("unit", &self.unit)
+            .field("sources", &sources)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: SourceCode> SourceCode for MappedSource<S> {
+    fn data(&self) -> &[u8] {
+        self.generated.data()
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.generated.name()
+    }
+
+    fn is_available(&self) -> bool {
+        self.generated.is_available()
+    }
+
+    fn origin(&self) -> SourceOrigin {
+        self.generated.origin()
+    }
+
+    fn line_starts(&self) -> Option<&[usize]> {
+        self.generated.line_starts()
+    }
+
+    fn remap_label(&self, label: &LabeledSpan) -> Option<LabeledSpan> {
+        let relabel = |text, span| {
+            let relabeled = LabeledSpan::new_with_kind(text, span, label.kind());
+            match LabeledSpan::style(label) {
+                Some(style) => relabeled.with_style(style),
+                None => relabeled,
+            }
+        };
+        if let Some((source, span)) = self.map_span(*label.inner()) {
+            let text = label.label().map(String::from);
+            return Some(relabel(text, span).with_source_code(Arc::clone(source)));
+        }
+        let text = label
+            .label()
+            .map_or_else(|| UNMAPPED_NOTE.into(), |text| format!("{text} ({UNMAPPED_NOTE})"));
+        Some(relabel(Some(text), *label.inner()))
+    }
+}
+
+/// Added to the text of labels that stay on the generated code.
+const UNMAPPED_NOTE: &str = "no source mapping, shown in generated code";
+
+/// Prefixes a source name with the map's `sourceRoot`.
+fn join_root(root: &str, source: &str) -> String {
+    if root.is_empty() || root.ends_with('/') {
+        format!("{root}{source}")
+    } else {
+        format!("{root}/{source}")
+    }
+}
+
+/// Decodes `mappings` into the segments of each generated line, sorted by
+/// generated column.
+fn decode_mappings(mappings: &str, sources: usize) -> Result<Vec<Vec<Segment>>, SourceMapError> {
+    let mut lines = Vec::new();
+    let mut previous = [0_i64; 5];
+    let mut offset = 0;
+    for line in mappings.split(';') {
+        let mut segments = Vec::new();
+        previous[0] = 0;
+        for text in line.split(',') {
+            let invalid = SourceMapError::InvalidMappings { offset };
+            offset += text.len() + 1;
+            if text.is_empty() {
+                continue;
+            }
+            let mut fields = [0_i64; 5];
+            let mut count = 0;
+            let mut bytes = text.bytes();
+            while bytes.len() > 0 {
+                let field = fields.get_mut(count).ok_or(invalid.clone())?;
+                *field = decode_vlq(&mut bytes).ok_or(invalid.clone())?;
+                count += 1;
+            }
+            if !matches!(count, 1 | 4 | 5) {
+                return Err(invalid);
+            }
+            for (value, field) in previous.iter_mut().zip(&fields[..count]) {
+                *value += field;
+            }
+            let field = |i: usize| u32::try_from(previous[i]).map_err(|_| invalid.clone());
+            let generated_column = field(0)?;
+            let original = if count == 1 {
+                None
+            } else {
+                let mapping = Mapping { source: field(1)?, line: field(2)?, column: field(3)? };
+                if mapping.source as usize >= sources {
+                    return Err(invalid);
+                }
+                Some(mapping)
+            };
+            segments.push(Segment { generated_column, original });
+        }
+        segments.sort_by_key(|segment| segment.generated_column);
+        lines.push(segments);
+    }
+    Ok(lines)
+}
+
+/// Decodes one base64 VLQ value, or `None` if it is malformed.
+fn decode_vlq(bytes: &mut impl Iterator<Item = u8>) -> Option<i64> {
+    let mut value = 0_i64;
+    let mut shift = 0;
+    loop {
+        let digit = match bytes.next()? {
+            c @ b'A'..=b'Z' => c - b'A',
+            c @ b'a'..=b'z' => c - b'a' + 26,
+            c @ b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        if shift > 32 {
+            return None;
+        }
+        value |= i64::from(digit & 0b1_1111) << shift;
+        shift += 5;
+        if digit & 0b10_0000 == 0 {
+            break;
+        }
+    }
+    let magnitude = value >> 1;
+    Some(if value & 1 == 1 { -magnitude } else { magnitude })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_vlq_values() {
+        let decode = |text: &str| decode_vlq(&mut text.bytes());
+        assert_eq!(decode("A"), Some(0));
+        assert_eq!(decode("C"), Some(1));
+        assert_eq!(decode("D"), Some(-1));
+        assert_eq!(decode("gB"), Some(16));
+        assert_eq!(decode("2H"), Some(123));
+        assert_eq!(decode("g"), None);
+        assert_eq!(decode("!"), None);
+        assert_eq!(decode("gggggggggggB"), None);
+    }
+
+    #[test]
+    fn decodes_relative_segments() {
+        let lines = decode_mappings("AAAA,IAAI;;ACCC,E", 2).unwrap();
+        let columns: Vec<Vec<_>> = lines
+            .iter()
+            .map(|line| line.iter().map(|s| (s.generated_column, s.original)).collect())
+            .collect();
+        let at = |source, line, column| Some(Mapping { source, line, column });
+        assert_eq!(columns[0], [(0, at(0, 0, 0)), (4, at(0, 0, 4))]);
+        assert!(columns[1].is_empty());
+        assert_eq!(columns[2], [(0, at(1, 1, 5)), (2, None)]);
+    }
+
+    #[test]
+    fn rejects_malformed_mappings() {
+        let error = |mappings| decode_mappings(mappings, 1).err();
+        assert_eq!(error("AA"), Some(SourceMapError::InvalidMappings { offset: 0 }));
+        assert_eq!(error("AAAA,CCAA"), Some(SourceMapError::InvalidMappings { offset: 5 }));
+        assert_eq!(error("AAAA;D"), Some(SourceMapError::InvalidMappings { offset: 5 }));
+    }
+}
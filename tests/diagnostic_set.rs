@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use miette::{
+    Diagnostic, DiagnosticSet, LabelKind, LabeledSpan, MietteDiagnostic, NamedSource, Severity,
+    SourceCode, SourceFragment, SourceOrigin,
+};
+
+fn source(name: &str) -> Arc<dyn SourceCode> {
+    Arc::new(NamedSource::new(name, "let a = 1;\nlet b = 2;\n"))
+}
+
+fn at(message: &str, name: &str, offset: u32) -> Box<dyn Diagnostic + Send + Sync> {
+    Box::new(
+        MietteDiagnostic::new(message)
+            .with_label(LabeledSpan::at(offset..offset + 1, "here"))
+            .with_source_code(source(name)),
+    )
+}
+
+fn messages(set: &DiagnosticSet) -> Vec<String> {
+    set.shown().iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn sorts_by_file_then_position() {
+    let mut set: DiagnosticSet = [
+        at("b.js second line", "b.js", 15),
+        at("a.js second line", "a.js", 11),
+        Box::new(MietteDiagnostic::new("no location")) as Box<dyn Diagnostic + Send + Sync>,
+        at("b.js first line", "b.js", 4),
+        at("a.js first line", "a.js", 0),
+    ]
+    .into_iter()
+    .collect();
+    set.sort();
+
+    assert_eq!(
+        messages(&set),
+        [
+            "no location",
+            "a.js first line",
+            "a.js second line",
+            "b.js first line",
+            "b.js second line"
+        ]
+    );
+}
+
+#[test]
+fn sorts_by_primary_label_in_the_enclosing_file() {
+    let fragment: Arc<dyn SourceCode> =
+        Arc::new(SourceFragment::new(NamedSource::new("a.vue", "x"), SourceOrigin::new(20, 2, 0)));
+    let mut set = DiagnosticSet::new();
+    set.push(Box::new(
+        MietteDiagnostic::new("in the script block")
+            .with_label(LabeledSpan::at(0..1, "here"))
+            .with_source_code(fragment),
+    ));
+    set.push(Box::new(
+        MietteDiagnostic::new("in the template")
+            .with_labels([
+                LabeledSpan::at(30..31, "secondary"),
+                LabeledSpan::at(5..6, "primary").with_kind(LabelKind::Primary),
+            ])
+            .with_source_code(source("a.vue")),
+    ));
+    set.sort();
+
+    assert_eq!(messages(&set), ["in the template", "in the script block"]);
+}
+
+#[test]
+fn dedup_keeps_the_first_of_identical_diagnostics() {
+    let mut set = DiagnosticSet::new();
+    set.extend([
+        at("unexpected token", "a.js", 4),
+        at("unexpected token", "b.js", 4),
+        at("unexpected token", "a.js", 4),
+        at("unexpected token", "a.js", 5),
+    ]);
+    set.dedup();
+
+    let locations: Vec<_> = set
+        .shown()
+        .iter()
+        .map(|diagnostic| {
+            (diagnostic.source_code().and_then(SourceCode::name), diagnostic.labels()[0].offset())
+        })
+        .collect();
+    assert_eq!(locations, [(Some("a.js"), 4), (Some("b.js"), 4), (Some("a.js"), 5)]);
+}
+
+#[test]
+fn counts_and_filters_by_severity() {
+    let mut set = DiagnosticSet::new();
+    set.extend([Severity::Advice, Severity::Warning, Severity::Advice].map(|severity| {
+        Box::new(MietteDiagnostic::new("lint").with_severity(severity))
+            as Box<dyn Diagnostic + Send + Sync>
+    }));
+    assert_eq!(set.count(Severity::Advice), 2);
+    assert!(!set.has_errors());
+
+    set.push(Box::new(MietteDiagnostic::new("no severity")));
+    assert_eq!(set.count(Severity::Error), 1);
+    assert!(set.has_errors());
+
+    set.retain_severity(Severity::Warning);
+    assert_eq!(set.len(), 2);
+    assert_eq!(set.count(Severity::Advice), 0);
+}
+
+#[test]
+fn limit_caps_the_shown_diagnostics() {
+    let mut set = DiagnosticSet::new().with_limit(2);
+    set.extend((0..5).map(|offset| at("unexpected token", "a.js", offset)));
+
+    assert_eq!(set.shown().len(), 2);
+    assert_eq!(set.omitted(), 3);
+    assert_eq!(set.count(Severity::Error), 5);
+    assert_eq!(set.summary().total(), 5);
+    assert_eq!(set.summary().files(), 1);
+
+    set.retain(|diagnostic| diagnostic.labels()[0].offset() == 4);
+    assert_eq!(set.shown().len(), 1);
+    assert_eq!(set.omitted(), 0);
+}
@@ -0,0 +1,242 @@
+use std::{borrow::Cow, fmt, sync::Arc};
+
+use miette::{
+    Applicability, Diagnostic, GraphicalReportHandler, GraphicalTheme, JSONDiagnostic,
+    JSONReportError, JSONReportHandler, LabelKind, LabeledSpan, MietteDiagnostic, NamedSource,
+    Severity, SourceCode, SourceFragment, SourceOrigin, Suggestion,
+};
+
+#[derive(Debug)]
+struct Report {
+    message: &'static str,
+    source: NamedSource<String>,
+    labels: Vec<LabeledSpan>,
+    suggestions: Vec<Suggestion>,
+    cause: Option<std::io::Error>,
+    related: Vec<Box<dyn Diagnostic + Send + Sync>>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+impl std::error::Error for Report {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause.as_ref().map(|cause| cause as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl Diagnostic for Report {
+    fn code(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed("config::invalid"))
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        Some(Severity::Warning)
+    }
+
+    fn help(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed("quote it as \"\\t\"\nor remove it"))
+    }
+
+    fn url(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed("https://example.com/rules?name=\"tab\""))
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&self.source)
+    }
+
+    fn labels(&self) -> &[LabeledSpan] {
+        &self.labels
+    }
+
+    fn suggestions(&self) -> &[Suggestion] {
+        &self.suggestions
+    }
+
+    fn related(&self) -> &[Box<dyn Diagnostic + Send + Sync>] {
+        &self.related
+    }
+}
+
+fn config() -> NamedSource<String> {
+    NamedSource::new("config.json", String::from("{\n\t\"indent\": \"\t\",\n}\n"))
+}
+
+fn schema() -> Arc<dyn SourceCode> {
+    Arc::new(NamedSource::new("schema.json", String::from("{\"indent\": \"number\"}\n")))
+}
+
+fn sources(name: &str) -> Option<Arc<dyn SourceCode>> {
+    match name {
+        "config.json" => Some(Arc::new(config())),
+        "schema.json" => Some(schema()),
+        _ => None,
+    }
+}
+
+fn report() -> Report {
+    Report {
+        message: "invalid `indent` \u{1} \u{1f980}",
+        source: config(),
+        labels: vec![
+            LabeledSpan::at(14..17, "expected a number"),
+            LabeledSpan::new_with_kind(None, (12, 0), LabelKind::Note),
+            LabeledSpan::at(11..19, "declared here").with_source_code(schema()),
+        ],
+        suggestions: vec![
+            Suggestion::new(14..17, "2")
+                .with_message("use a width")
+                .with_applicability(Applicability::MaybeIncorrect),
+            Suggestion::new(17..18, ""),
+        ],
+        cause: Some(std::io::Error::other("tab characters are not allowed")),
+        related: vec![Box::new(
+            MietteDiagnostic::new("trailing comma")
+                .with_severity(Severity::Advice)
+                .with_label(LabeledSpan::at(18..19, "here")),
+        )],
+    }
+}
+
+fn graphical(diagnostic: &dyn Diagnostic) -> String {
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .with_width(80)
+        .with_links(false)
+        .render_report(&mut output, diagnostic)
+        .unwrap();
+    output
+}
+
+fn json(diagnostic: &dyn Diagnostic) -> String {
+    let mut output = String::new();
+    JSONReportHandler::new().render_report(&mut output, diagnostic).unwrap();
+    output
+}
+
+#[test]
+fn round_trips_through_the_graphical_renderer() {
+    let original = report();
+    let parsed = JSONDiagnostic::from_json_with_sources(&json(&original), sources).unwrap();
+
+    assert_eq!(parsed.filename(), Some("config.json"));
+    assert_eq!(graphical(&parsed), graphical(&original));
+}
+
+#[test]
+fn round_trips_through_the_json_renderer() {
+    let output = json(&report());
+    let parsed = JSONDiagnostic::from_json_with_sources(&output, sources).unwrap();
+    assert_eq!(json(&parsed), output);
+
+    // Without sources, labels keep their spans but have nothing to render against.
+    let parsed = JSONDiagnostic::from_json(&output).unwrap();
+    assert!(parsed.source_code().is_none());
+    let spans = |diagnostic: &dyn Diagnostic| {
+        let labels = diagnostic.labels().iter();
+        labels.map(|label| (label.label().map(String::from), *label.inner())).collect::<Vec<_>>()
+    };
+    assert_eq!(spans(&parsed), spans(&report()));
+    assert!(parsed.labels().iter().all(|label| label.source_code().is_none()));
+    assert_eq!(parsed.suggestions(), report().suggestions());
+    assert_eq!(parsed.related()[0].to_string(), "trailing comma");
+}
+
+#[test]
+fn missing_optional_fields_use_defaults() {
+    let parsed = JSONDiagnostic::from_json(
+        r#"{"message": "oops","labels": [{"span": {"offset": 1,"length": 2}}]}"#,
+    )
+    .unwrap();
+
+    assert_eq!(parsed.to_string(), "oops");
+    assert_eq!(parsed.code(), None);
+    assert_eq!(parsed.severity(), None);
+    assert_eq!(parsed.help(), None);
+    assert_eq!(parsed.filename(), None);
+    assert!(std::error::Error::source(&parsed).is_none());
+    assert_eq!(parsed.labels(), [LabeledSpan::new_with_kind(None, (1, 2), LabelKind::Secondary)]);
+    assert!(parsed.suggestions().is_empty());
+    assert!(parsed.related().is_empty());
+}
+
+#[test]
+fn invalid_reports_are_rejected() {
+    assert_eq!(
+        JSONDiagnostic::from_json(r#"{"message": "oops""#).unwrap_err(),
+        JSONReportError::Json { offset: 18, message: "expected `,` or `}`" }
+    );
+    for (json, field) in [
+        (r#"{"code": "x"}"#, "message"),
+        (r#"{"message": 1}"#, "message"),
+        (r#"{"message": "x","severity": "fatal"}"#, "severity"),
+        (r#"{"message": "x","causes": [1]}"#, "causes"),
+        (r#"{"message": "x","labels": [{}]}"#, "span"),
+        (r#"{"message": "x","labels": [{"span": {"offset": -1,"length": 0}}]}"#, "offset"),
+        (r#"{"message": "x","labels": [{"kind": "main","span": {}}]}"#, "kind"),
+    ] {
+        assert_eq!(
+            JSONDiagnostic::from_json(json).unwrap_err(),
+            JSONReportError::InvalidField(field),
+            "{json}"
+        );
+    }
+}
+
+#[test]
+fn round_trips_diagnostics_without_a_severity() {
+    let original = MietteDiagnostic::new("unexpected token")
+        .with_label(LabeledSpan::at(2..5, "here"))
+        .with_source_code(Arc::new(config()));
+    assert_eq!(original.severity(), None);
+
+    // Reports give every diagnostic a severity, and `None` renders the same
+    // as `Some(Severity::Error)`.
+    let output = json(&original);
+    assert!(output.contains(r#""severity": "error""#), "{output}");
+    let parsed = JSONDiagnostic::from_json_with_sources(&output, sources).unwrap();
+    assert_eq!(parsed.severity(), Some(Severity::Error));
+    assert_eq!(json(&parsed), output);
+    assert_eq!(graphical(&parsed), graphical(&original));
+}
+
+#[test]
+fn round_trips_fragment_sources() {
+    let file = "<template/>\n<script>\nlet a = ;\n</script>\n";
+    let start = file.find("let").unwrap();
+    let origin = SourceOrigin::new(u32::try_from(start).unwrap(), 2, 0);
+    let fragment = || -> Arc<dyn SourceCode> {
+        Arc::new(NamedSource::new(
+            "App.vue",
+            SourceFragment::new(&file[start..file.len() - 10], origin),
+        ))
+    };
+    let original = MietteDiagnostic::new("expected an expression")
+        .with_severity(Severity::Error)
+        .with_label(LabeledSpan::at(8..9, "here"))
+        .with_source_code(fragment());
+
+    let output = json(&original);
+    assert!(
+        output.contains(r#""span": {"offset": 29,"length": 1,"line": 3,"column": 9}"#),
+        "{output}"
+    );
+    let parsed =
+        JSONDiagnostic::from_json_with_sources(&output, |name| (name == "App.vue").then(fragment))
+            .unwrap();
+    assert_eq!(parsed.labels(), original.labels());
+    assert_eq!(json(&parsed), output);
+    assert_eq!(graphical(&parsed), graphical(&original));
+
+    // A source attached after parsing moves the spans the same way.
+    let parsed = JSONDiagnostic::from_json(&output).unwrap();
+    assert_eq!(parsed.labels()[0].offset(), 29);
+    let parsed = parsed.with_source_code(fragment());
+    assert_eq!(parsed.labels(), original.labels());
+    assert_eq!(json(&parsed), output);
+    assert_eq!(graphical(&parsed), graphical(&original));
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use miette::{
+    Diagnostic, GraphicalReportHandler, GraphicalTheme, LabeledSpan, MietteDiagnostic, NamedSource,
+    Severity, SourceCode,
+};
+
+fn source() -> Arc<dyn SourceCode> {
+    Arc::new(NamedSource::new("test.js", String::from("let ? = 1;")))
+}
+
+fn diagnostic() -> MietteDiagnostic {
+    MietteDiagnostic::new("unexpected token")
+        .with_code("parser::unexpected")
+        .with_severity(Severity::Warning)
+        .with_help("remove it")
+        .with_note("`?` is not an identifier")
+        .with_url("https://oxc.rs/docs")
+        .with_label(LabeledSpan::at(4..5, "here"))
+        .with_source_code(source())
+}
+
+#[test]
+fn builder_sets_every_field() {
+    let diagnostic = diagnostic();
+    assert_eq!(diagnostic.to_string(), "unexpected token");
+    assert_eq!(diagnostic.code().as_deref(), Some("parser::unexpected"));
+    assert_eq!(diagnostic.severity(), Some(Severity::Warning));
+    assert_eq!(diagnostic.help().as_deref(), Some("remove it"));
+    assert_eq!(diagnostic.note().as_deref(), Some("`?` is not an identifier"));
+    assert_eq!(diagnostic.url().as_deref(), Some("https://oxc.rs/docs"));
+    assert_eq!(diagnostic.labels(), [LabeledSpan::at(4..5, "here")]);
+    assert_eq!(diagnostic.source_code().and_then(SourceCode::name), Some("test.js"));
+}
+
+#[test]
+fn diagnostics_compare_structurally() {
+    let original = diagnostic();
+    assert_eq!(original.clone(), original);
+    assert_eq!(original, diagnostic());
+    assert_ne!(diagnostic(), diagnostic().with_help("delete it"));
+    assert_ne!(
+        diagnostic(),
+        diagnostic().with_source_code(Arc::new(NamedSource::new("other.js", "let ? = 1;")))
+    );
+}
+
+#[test]
+fn diagnostics_are_send_and_sync() {
+    let boxed: Box<dyn Diagnostic + Send + Sync> = Box::new(diagnostic());
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .with_width(80)
+        .with_links(false)
+        .render_report(&mut output, &*boxed)
+        .unwrap();
+    assert!(output.contains("parser::unexpected: unexpected token"), "{output}");
+    assert!(output.contains("[test.js:1:5]"), "{output}");
+}
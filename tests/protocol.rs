@@ -1,6 +1,6 @@
-use std::fmt;
+use std::{fmt, ops::Range};
 
-use miette::{Diagnostic, SourceSpan};
+use miette::{Diagnostic, LabeledSpan, SourceSpan};
 
 #[derive(Debug)]
 struct TestDiagnostic;
@@ -21,6 +21,104 @@ fn spans_convert_from_offsets_and_ranges() {
     assert_eq!(SourceSpan::from(3..7), SourceSpan::from((3, 4)));
 }
 
+fn span(range: Range<u32>) -> SourceSpan {
+    SourceSpan::from(range)
+}
+
+#[test]
+fn span_ends_saturate_at_u32_max() {
+    assert_eq!(span(3..7).end(), 7);
+    assert_eq!(span(3..7).to_range(), 3..7);
+    let overflowing = SourceSpan::from((u32::MAX - 1, 5));
+    assert_eq!(overflowing.end(), u32::MAX);
+    assert_eq!(overflowing.to_range(), u32::MAX - 1..u32::MAX);
+    assert_eq!(Range::<usize>::from(overflowing), (u32::MAX - 1) as usize..u32::MAX as usize);
+}
+
+#[test]
+fn spans_join_and_intersect() {
+    assert_eq!(span(2..4).join(span(8..9)), span(2..9));
+    assert_eq!(span(8..9).join(span(2..4)), span(2..9));
+    assert_eq!(span(2..9).join(span(4..5)), span(2..9));
+    assert_eq!(span(0..1).join(SourceSpan::from((u32::MAX, 0))), span(0..u32::MAX));
+    assert_eq!(SourceSpan::from((u32::MAX - 1, 5)).join(span(0..1)), span(0..u32::MAX));
+
+    assert_eq!(span(2..6).intersect(span(4..9)), Some(span(4..6)));
+    assert_eq!(span(2..9).intersect(span(4..6)), Some(span(4..6)));
+    assert_eq!(span(2..6).intersect(span(6..9)), Some(span(6..6)));
+    assert_eq!(span(2..6).intersect(span(7..9)), None);
+    assert_eq!(span(7..9).intersect(span(2..6)), None);
+    let at_max = SourceSpan::from((u32::MAX, 0));
+    assert_eq!(SourceSpan::from((u32::MAX - 1, 5)).intersect(at_max), Some(at_max));
+}
+
+#[test]
+fn spans_contain_offsets_and_spans() {
+    let s = span(2..6);
+    assert!(!s.contains(1));
+    assert!(s.contains(2));
+    assert!(s.contains(5));
+    assert!(!s.contains(6));
+    assert!(!span(2..2).contains(2));
+    assert!(!span(0..u32::MAX).contains(u32::MAX));
+    assert!(span(0..u32::MAX).contains(u32::MAX - 1));
+
+    assert!(s.contains_span(s));
+    assert!(s.contains_span(span(3..4)));
+    assert!(s.contains_span(span(6..6)));
+    assert!(!s.contains_span(span(5..7)));
+    assert!(!s.contains_span(span(1..3)));
+    assert!(span(0..u32::MAX).contains_span(SourceSpan::from((u32::MAX, 0))));
+}
+
+#[test]
+fn spans_shift_and_rebase_checked() {
+    let s = span(4..6);
+    assert_eq!(s.shift(0), Some(s));
+    assert_eq!(s.shift(10), Some(span(14..16)));
+    assert_eq!(s.shift(-4), Some(span(0..2)));
+    assert_eq!(s.shift(-5), None);
+    assert_eq!(s.shift(i64::from(u32::MAX) - 6), Some(span(u32::MAX - 2..u32::MAX)));
+    assert_eq!(s.shift(i64::from(u32::MAX) - 5), None);
+    assert_eq!(s.shift(i64::MIN), None);
+    assert_eq!(s.shift(i64::MAX), None);
+    assert_eq!(SourceSpan::from((u32::MAX - 1, 5)).shift(0), None);
+
+    assert_eq!(span(14..16).rebase(10), Some(span(4..6)));
+    assert_eq!(span(14..16).rebase(14), Some(span(0..2)));
+    assert_eq!(span(14..16).rebase(15), None);
+    assert_eq!(span(14..16).rebase(u32::MAX), None);
+    assert_eq!(SourceSpan::from((u32::MAX, 0)).rebase(u32::MAX), Some(span(0..0)));
+}
+
+#[test]
+fn spans_convert_from_usize_ranges() {
+    assert_eq!(SourceSpan::try_from(3usize..7), Ok(span(3..7)));
+    let (start, end) = (7usize, 3usize);
+    assert_eq!(SourceSpan::try_from(start..end), Ok(span(7..7)));
+    let max = u32::MAX as usize;
+    assert_eq!(SourceSpan::try_from(0..max), Ok(span(0..u32::MAX)));
+    assert!(SourceSpan::try_from(0..max + 1).is_err());
+    assert!(SourceSpan::try_from(max + 1..max + 2).is_err());
+    assert_eq!(Range::<usize>::from(span(3..7)), 3..7);
+}
+
+#[test]
+fn labels_share_span_operations() {
+    let label = LabeledSpan::at(4..6, "here");
+    assert_eq!(label.end(), 6);
+    assert_eq!(label.to_range(), 4..6);
+    assert!(label.contains(5));
+    assert!(label.contains_span(span(4..5)));
+    assert_eq!(label.clone().join(span(8..10)), LabeledSpan::at(4..10, "here"));
+    assert_eq!(label.clone().intersect(span(5..10)), Some(LabeledSpan::at(5..6, "here")));
+    assert_eq!(label.clone().intersect(span(7..10)), None);
+    assert_eq!(label.clone().shift(2), Some(LabeledSpan::at(6..8, "here")));
+    assert_eq!(label.clone().shift(-5), None);
+    assert_eq!(label.clone().rebase(4), Some(LabeledSpan::at(0..2, "here")));
+    assert_eq!(label.rebase(5), None);
+}
+
 #[test]
 fn diagnostics_can_be_owned_as_trait_objects() {
     let diagnostic: Box<dyn Diagnostic + Send + Sync> = Box::new(TestDiagnostic);
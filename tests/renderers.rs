@@ -1,9 +1,12 @@
-use std::{borrow::Cow, fmt};
+use std::{borrow::Cow, fmt, sync::Arc};
 
 use miette::{
-    Diagnostic, GraphicalReportHandler, GraphicalTheme, JSONReportHandler, LabeledSpan,
-    NamedSource, Severity, SourceCode,
+    Applicability, CheckstyleReportHandler, CodeQualityReportHandler, CodeQualitySeverity,
+    ColumnUnit, Diagnostic, DiagnosticSummary, GitHubActionsReportHandler, GraphicalReportHandler,
+    GraphicalTheme, JSONReportHandler, JUnitReportHandler, LabelKind, LabeledSpan, LineColumn,
+    MietteDiagnostic, NamedSource, ReportHandler, Severity, SourceCode, SourceIndex, Suggestion,
 };
+use owo_colors::{OwoColorize, Style};
 
 #[derive(Debug)]
 struct TestDiagnostic {
@@ -73,3 +76,1183 @@ fn json_renderer_is_explicit() {
     assert!(output.contains(r#""filename": "test.js""#));
     assert!(output.contains(r#""label": "here""#));
 }
+
+/// Implemented by hand, as [`MietteDiagnostic`] has no related diagnostics.
+#[derive(Debug)]
+struct DuplicateKey {
+    source: NamedSource<String>,
+    labels: [LabeledSpan; 1],
+    related: Vec<Box<dyn Diagnostic + Send + Sync>>,
+}
+
+impl fmt::Display for DuplicateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("duplicate key `a`")
+    }
+}
+
+impl std::error::Error for DuplicateKey {}
+
+impl Diagnostic for DuplicateKey {
+    fn labels(&self) -> &[LabeledSpan] {
+        &self.labels
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&self.source)
+    }
+
+    fn related(&self) -> &[Box<dyn Diagnostic + Send + Sync>] {
+        &self.related
+    }
+}
+
+fn duplicate_key() -> DuplicateKey {
+    DuplicateKey {
+        source: NamedSource::new("test.json", String::from("{\n  \"a\": 1,\n  \"a\": 2\n}\n")),
+        labels: [LabeledSpan::at(14..17, "duplicate")],
+        related: vec![Box::new(
+            MietteDiagnostic::new("first defined here")
+                .with_code("json::previous")
+                .with_severity(Severity::Advice)
+                .with_help("remove one of them")
+                .with_label(LabeledSpan::at(4..7, "here")),
+        )],
+    }
+}
+
+#[test]
+fn graphical_renderer_nests_related_diagnostics() {
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .with_width(80)
+        .with_links(false)
+        .render_report(&mut output, &duplicate_key())
+        .unwrap();
+
+    let expected = r#"
+  x duplicate key `a`
+   ,-[test.json:3:3]
+ 2 |   "a": 1,
+ 3 |   "a": 2
+   :   ^|^
+   :    `-- duplicate
+ 4 | }
+   `----
+
+      > json::previous: first defined here
+       ,-[test.json:2:3]
+     1 | {
+     2 |   "a": 1,
+       :   ^|^
+       :    `-- here
+     3 |   "a": 2
+       `----
+      help: remove one of them
+"#;
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn json_renderer_nests_related_diagnostics() {
+    let mut output = String::new();
+    JSONReportHandler::new().render_report(&mut output, &duplicate_key()).unwrap();
+
+    assert_eq!(
+        output,
+        concat!(
+            r#"{"message": "duplicate key `a`","severity": "error","causes": [],"#,
+            r#""filename": "test.json","labels": [{"label": "duplicate","kind": "secondary","#,
+            r#""filename": "test.json","#,
+            r#""span": {"offset": 14,"length": 3,"line": 3,"column": 3}}],"suggestions": [],"#,
+            r#""related": [{"message": "first defined here","code": "json::previous","#,
+            r#""severity": "advice","causes": [],"help": "remove one of them","#,
+            r#""filename": "test.json","labels": [{"label": "here","kind": "secondary","#,
+            r#""filename": "test.json","#,
+            r#""span": {"offset": 4,"length": 3,"line": 2,"column": 3}}],"suggestions": [],"#,
+            r#""related": []}]}"#
+        )
+    );
+}
+
+/// Implemented by hand, as [`MietteDiagnostic`] has no an `Error::source` chain.
+#[derive(Debug)]
+struct LoadConfig {
+    cause: LoadCause,
+}
+
+#[derive(Debug)]
+enum LoadCause {
+    Io(std::io::Error),
+    Parse(TestDiagnostic),
+}
+
+impl fmt::Display for LoadConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to load config")
+    }
+}
+
+impl std::error::Error for LoadConfig {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.cause {
+            LoadCause::Io(error) => Some(error),
+            LoadCause::Parse(diagnostic) => Some(diagnostic),
+        }
+    }
+}
+
+impl Diagnostic for LoadConfig {
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        match &self.cause {
+            LoadCause::Io(_) => None,
+            LoadCause::Parse(diagnostic) => Some(diagnostic),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Wrapped(&'static str, std::io::Error);
+
+impl fmt::Display for Wrapped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for Wrapped {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.1)
+    }
+}
+
+fn io_cause() -> LoadConfig {
+    let inner = std::io::Error::other("permission denied");
+    LoadConfig {
+        cause: LoadCause::Io(std::io::Error::other(Wrapped(
+            "could not read `oxlintrc.json`",
+            inner,
+        ))),
+    }
+}
+
+#[test]
+fn graphical_renderer_walks_error_sources() {
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .with_width(80)
+        .render_report(&mut output, &io_cause())
+        .unwrap();
+
+    let expected = "
+  x failed to load config
+
+  caused by: could not read `oxlintrc.json`
+  caused by: permission denied
+";
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn graphical_renderer_renders_diagnostic_sources_inline() {
+    let diagnostic = LoadConfig { cause: LoadCause::Parse(diagnostic()) };
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .with_width(80)
+        .with_links(false)
+        .render_report(&mut output, &diagnostic)
+        .unwrap();
+
+    let expected = "
+  x failed to load config
+
+  caused by: parser::unexpected: unexpected token
+       ,-[test.js:1:5]
+     1 | let ? = 1;
+       :     |
+       :     `-- here
+       `----
+      help: remove it
+";
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn json_renderer_lists_causes() {
+    let mut output = String::new();
+    JSONReportHandler::new().render_report(&mut output, &io_cause()).unwrap();
+    assert!(
+        output.contains(r#""causes": ["could not read `oxlintrc.json`","permission denied"]"#),
+        "{output}"
+    );
+
+    let mut output = String::new();
+    let diagnostic = LoadConfig { cause: LoadCause::Parse(diagnostic()) };
+    JSONReportHandler::new().render_report(&mut output, &diagnostic).unwrap();
+    assert!(output.contains(r#""causes": ["unexpected token"]"#), "{output}");
+}
+
+/// Implemented by hand, as [`MietteDiagnostic`] has no suggestions.
+#[derive(Debug)]
+struct PreferLet {
+    source: NamedSource<String>,
+    labels: [LabeledSpan; 1],
+    suggestions: Vec<Suggestion>,
+}
+
+impl fmt::Display for PreferLet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unexpected `var`")
+    }
+}
+
+impl std::error::Error for PreferLet {}
+
+impl Diagnostic for PreferLet {
+    fn labels(&self) -> &[LabeledSpan] {
+        &self.labels
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&self.source)
+    }
+
+    fn suggestions(&self) -> &[Suggestion] {
+        &self.suggestions
+    }
+}
+
+fn prefer_let(suggestions: Vec<Suggestion>) -> PreferLet {
+    PreferLet {
+        source: NamedSource::new("test.js", String::from("\tvar x = 1;\nfoo(x);\n")),
+        labels: [LabeledSpan::underline(1..4)],
+        suggestions,
+    }
+}
+
+#[test]
+fn graphical_renderer_draws_suggestions_as_diffs() {
+    let diagnostic = prefer_let(vec![
+        Suggestion::new(1..4, "let")
+            .with_message("use `let` instead")
+            .with_applicability(Applicability::MachineApplicable),
+        Suggestion::new(9..11, "1;\nconsole.log(x);"),
+    ]);
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .with_width(80)
+        .render_report(&mut output, &diagnostic)
+        .unwrap();
+
+    let expected = "
+  x unexpected `var`
+   ,-[test.js:1:2]
+ 1 |     var x = 1;
+   :     ^^^
+ 2 | foo(x);
+   `----
+  suggestion: use `let` instead
+   ,-[test.js:1:2]
+ 1 | -     var x = 1;
+ 1 | +     let x = 1;
+   `----
+   ,-[test.js:1:10]
+ 1 | -     var x = 1;
+ 1 | +     var x = 1;
+ 2 | + console.log(x);
+   `----
+";
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn graphical_renderer_skips_suggestions_past_the_source() {
+    let diagnostic = prefer_let(vec![Suggestion::new(18..40, ""), Suggestion::new(1..4, "let")]);
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .with_width(80)
+        .render_report(&mut output, &diagnostic)
+        .unwrap();
+
+    let expected = "
+  x unexpected `var`
+   ,-[test.js:1:2]
+ 1 |     var x = 1;
+   :     ^^^
+ 2 | foo(x);
+   `----
+   ,-[test.js:1:2]
+ 1 | -     var x = 1;
+ 1 | +     let x = 1;
+   `----
+";
+    assert_eq!(output, expected);
+}
+
+/// The diff drawn for a suggestion replacing `span` of `text`, without the
+/// snippet above it.
+fn suggestion_diff(text: &str, span: std::ops::Range<u32>, replacement: &str) -> String {
+    let diagnostic = PreferLet {
+        source: NamedSource::new("test.js", text.to_owned()),
+        labels: [LabeledSpan::underline(0..0)],
+        suggestions: vec![Suggestion::new(span, replacement).with_message("fix it")],
+    };
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .with_width(80)
+        .render_report(&mut output, &diagnostic)
+        .unwrap();
+    output.split_once("suggestion: ").map(|(_, diff)| diff.to_owned()).unwrap_or(output)
+}
+
+#[test]
+fn graphical_renderer_draws_suggestions_across_line_breaks() {
+    let deleted = "fix it\n   ,-[test.js:1:1]\n 1 | - x = 1\n   `----\n";
+    assert_eq!(suggestion_diff("x = 1\ny\n", 0..6, ""), deleted);
+    assert_eq!(suggestion_diff("x = 1\n", 0..6, ""), deleted);
+    assert_eq!(
+        suggestion_diff("let a\n", 5..6, ";\n"),
+        "fix it\n   ,-[test.js:1:6]\n 1 | - let a\n 1 | + let a;\n   `----\n"
+    );
+    assert_eq!(
+        suggestion_diff("a\nb", 1..2, " "),
+        "fix it\n   ,-[test.js:1:2]\n 1 | - a\n 2 | - b\n 1 | + a b\n   `----\n"
+    );
+    assert_eq!(
+        suggestion_diff("a\nb\n", 4..4, "c\n"),
+        "fix it\n   ,-[test.js:3:1]\n 3 | + c\n   `----\n"
+    );
+    assert_eq!(
+        suggestion_diff("a\n", 2..2, "b"),
+        "fix it\n   ,-[test.js:2:1]\n 2 | + b\n   `----\n"
+    );
+    assert_eq!(
+        suggestion_diff("", 0..0, "let a;"),
+        "fix it\n   ,-[test.js:1:1]\n 1 | + let a;\n   `----\n"
+    );
+    // Spans that cannot be read still show their message.
+    assert_eq!(suggestion_diff("a\n", 5..6, ""), "fix it\n");
+}
+
+#[test]
+fn json_renderer_emits_suggestions() {
+    let diagnostic = prefer_let(vec![
+        Suggestion::new(1..4, "let")
+            .with_message("use \"let\"")
+            .with_applicability(Applicability::MachineApplicable),
+        Suggestion::new(12..12, "\n"),
+    ]);
+    let mut output = String::new();
+    JSONReportHandler::new().render_report(&mut output, &diagnostic).unwrap();
+
+    assert!(
+        output.contains(concat!(
+            r#""suggestions": [{"message": "use \"let\"","replacement": "let","#,
+            r#""applicability": "machine-applicable","#,
+            r#""span": {"offset": 1,"length": 3,"line": 1,"column": 2}},"#,
+            r#"{"replacement": "\n","applicability": "unspecified","#,
+            r#""span": {"offset": 12,"length": 0,"line": 2,"column": 1}}]"#,
+        )),
+        "{output}"
+    );
+}
+
+fn type_mismatch() -> MietteDiagnostic {
+    let types: Arc<dyn SourceCode> =
+        Arc::new(NamedSource::new("types.d.ts", String::from("// ids\ntype Id = string;\n")));
+    MietteDiagnostic::new("type `Id` conflicts with its usage")
+        .with_labels([
+            LabeledSpan::at(15..16, "used as a number here"),
+            LabeledSpan::at(12..14, "declared here").with_source_code(types),
+            LabeledSpan::at(10..12, "annotated here"),
+        ])
+        .with_source_code(Arc::new(NamedSource::new("app.ts", String::from("const id: Id = 1;\n"))))
+}
+
+#[test]
+fn graphical_renderer_groups_labels_by_source() {
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .with_width(80)
+        .render_report(&mut output, &type_mismatch())
+        .unwrap();
+
+    let expected = "
+  x type `Id` conflicts with its usage
+   ,-[app.ts:1:11]
+ 1 | const id: Id = 1;
+   :           ^|   |
+   :            |   `-- used as a number here
+   :            `-- annotated here
+   `----
+   ,-[types.d.ts:2:6]
+ 1 | // ids
+ 2 | type Id = string;
+   :      ^|
+   :       `-- declared here
+   `----
+";
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn json_renderer_reports_each_label_filename() {
+    let mut output = String::new();
+    JSONReportHandler::new().render_report(&mut output, &type_mismatch()).unwrap();
+
+    assert!(
+        output.contains(concat!(
+            r#"{"label": "declared here","kind": "secondary","filename": "types.d.ts","#,
+            r#""span": {"offset": 12,"length": 2,"line": 2,"column": 6}}"#
+        )),
+        "{output}"
+    );
+    assert!(
+        output.contains(concat!(
+            r#"{"label": "annotated here","kind": "secondary","filename": "app.ts","#,
+            r#""span": {"offset": 10,"length": 2,"line": 1,"column": 11}}"#
+        )),
+        "{output}"
+    );
+}
+
+fn shadowed(labels: Vec<LabeledSpan>) -> MietteDiagnostic {
+    MietteDiagnostic::new("`x` is shadowed")
+        .with_labels(labels)
+        .with_source_code(Arc::new(NamedSource::new("test.js", "let x = 1; let x = 2;")))
+}
+
+fn kinds() -> Vec<LabeledSpan> {
+    vec![
+        LabeledSpan::new_with_kind(Some("kept".into()), 0..3, LabelKind::Note),
+        LabeledSpan::at(4..5, "first"),
+        LabeledSpan::new_with_kind(Some("removed".into()), 8..10, LabelKind::Removed),
+        LabeledSpan::new_with_kind(Some("added".into()), 11..14, LabelKind::Added),
+        LabeledSpan::new_primary_with_span(Some("second".into()), 15..20),
+    ]
+}
+
+#[test]
+fn graphical_renderer_draws_label_kinds() {
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .with_width(80)
+        .with_links(false)
+        .render_report(&mut output, &shadowed(kinds()))
+        .unwrap();
+
+    let expected = "
+  × `x` is shadowed
+   ╭─[test.js:1:16]
+ 1 │ let x = 1; let x = 2;
+   · ┄┬┄ ┬   -┬ +┬+ ━━┯━━
+   ·  │  │    │  │    ╰── second
+   ·  │  │    │  ╰── added
+   ·  │  │    ╰── removed
+   ·  │  ╰── first
+   ·  ╰── kept
+   ╰────
+";
+    assert_eq!(output, expected);
+
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .with_width(80)
+        .render_report(&mut output, &shadowed(kinds()))
+        .unwrap();
+    assert!(output.contains(" : .|. |   -| +|+ ~~^~~\n"), "{output}");
+}
+
+#[test]
+fn graphical_renderer_draws_primary_labels_apart_from_secondary_ones() {
+    let labels = || {
+        vec![
+            LabeledSpan::at(4..5, "first"),
+            LabeledSpan::new_primary_with_span(Some("second".into()), 15..16),
+        ]
+    };
+    let render = |theme, labels| {
+        let mut output = String::new();
+        GraphicalReportHandler::new_themed(theme)
+            .with_width(80)
+            .with_links(false)
+            .render_report(&mut output, &shadowed(labels))
+            .unwrap();
+        output
+    };
+
+    // Secondary labels look as they always have.
+    let secondary = vec![LabeledSpan::at(4..5, "first"), LabeledSpan::at(15..16, "second")];
+    let expected = "
+  × `x` is shadowed
+   ╭─[test.js:1:5]
+ 1 │ let x = 1; let x = 2;
+   ·     ┬          ┬
+   ·     │          ╰── second
+   ·     ╰── first
+   ╰────
+";
+    assert_eq!(render(GraphicalTheme::unicode_nocolor(), secondary.clone()), expected);
+    let expected = "
+  x `x` is shadowed
+   ,-[test.js:1:5]
+ 1 | let x = 1; let x = 2;
+   :     |          |
+   :     |          `-- second
+   :     `-- first
+   `----
+";
+    assert_eq!(render(GraphicalTheme::none(), secondary), expected);
+
+    // A primary label is underlined with a character of its own.
+    let expected = "
+  × `x` is shadowed
+   ╭─[test.js:1:16]
+ 1 │ let x = 1; let x = 2;
+   ·     ┬          ┯
+   ·     │          ╰── second
+   ·     ╰── first
+   ╰────
+";
+    assert_eq!(render(GraphicalTheme::unicode_nocolor(), labels()), expected);
+    let expected = "
+  x `x` is shadowed
+   ,-[test.js:1:16]
+ 1 | let x = 1; let x = 2;
+   :     |          ^
+   :     |          `-- second
+   :     `-- first
+   `----
+";
+    assert_eq!(render(GraphicalTheme::none(), labels()), expected);
+
+    let wide = vec![
+        LabeledSpan::at(0..3, "first"),
+        LabeledSpan::new_primary_with_span(Some("second".into()), 11..14),
+    ];
+    let output = render(GraphicalTheme::none(), wide);
+    assert!(output.contains(" : ^|^        ~^~\n"), "{output}");
+}
+
+#[test]
+fn graphical_renderer_applies_label_style_overrides() {
+    let style = Style::new().underline();
+    let labels = vec![LabeledSpan::at(4..5, "first").with_style(style)];
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode())
+        .with_width(80)
+        .with_links(false)
+        .render_report(&mut output, &shadowed(labels))
+        .unwrap();
+
+    assert!(output.contains(&"first".style(style).to_string()), "{output:?}");
+}
+
+#[test]
+fn json_renderer_emits_label_kinds() {
+    let mut output = String::new();
+    JSONReportHandler::new().render_report(&mut output, &shadowed(kinds())).unwrap();
+
+    for kind in [r#""kind": "note""#, r#""kind": "secondary""#, r#""kind": "removed""#] {
+        assert!(output.contains(kind), "{output}");
+    }
+    assert!(
+        output.contains(concat!(
+            r#"{"label": "second","kind": "primary","filename": "test.js","#,
+            r#""span": {"offset": 15,"length": 5,"line": 1,"column": 16}}"#
+        )),
+        "{output}"
+    );
+    assert!(output.contains(r#"{"label": "added","kind": "added","#), "{output}");
+}
+
+fn lints() -> Vec<MietteDiagnostic> {
+    let file = |name: &str| -> Arc<dyn SourceCode> { Arc::new(NamedSource::new(name, "")) };
+    let lint = |code: &str, severity, name| {
+        MietteDiagnostic::new("lint")
+            .with_code(code)
+            .with_severity(severity)
+            .with_source_code(file(name))
+    };
+    vec![
+        lint("no-debugger", Severity::Error, "a.js"),
+        lint("no-unused-vars", Severity::Warning, "a.js"),
+        lint("no-unused-vars", Severity::Warning, "b.js"),
+        lint("eqeqeq", Severity::Warning, "c.js"),
+        lint("no-\"quote\"", Severity::Warning, "c.js"),
+        MietteDiagnostic::new("no files given").with_severity(Severity::Advice),
+    ]
+}
+
+fn summary(diagnostics: &[MietteDiagnostic]) -> DiagnosticSummary {
+    DiagnosticSummary::new(diagnostics.iter().map(|diagnostic| diagnostic as &dyn Diagnostic))
+}
+
+#[test]
+fn graphical_renderer_summarizes_batches() {
+    let handler = GraphicalReportHandler::new_themed(GraphicalTheme::none());
+    let render = |summary: &DiagnosticSummary| {
+        let mut output = String::new();
+        handler.render_summary(&mut output, summary).unwrap();
+        output
+    };
+
+    let lints = lints();
+    assert_eq!(
+        render(&summary(&lints).with_top_codes(2)),
+        "Found 1 error, 4 warnings and 1 advice in 3 files.\n\
+         Most frequent codes:\n  2 no-unused-vars\n  1 eqeqeq\n"
+    );
+    assert_eq!(render(&summary(&lints[1..3])), "Found 2 warnings in 2 files.\n");
+    assert_eq!(render(&summary(&lints[..1])), "Found 1 error in 1 file.\n");
+    assert_eq!(render(&summary(&[])), "No problems found.\n");
+
+    let mut output = String::new();
+    let theme = GraphicalTheme::unicode();
+    GraphicalReportHandler::new_themed(theme.clone())
+        .render_summary(&mut output, &summary(&lints[1..3]))
+        .unwrap();
+    assert!(output.contains(&"2 warnings".style(theme.warning_style()).to_string()), "{output:?}");
+}
+
+#[test]
+fn json_renderer_summarizes_batches() {
+    let mut output = String::new();
+    JSONReportHandler::new()
+        .render_summary(&mut output, &summary(&lints()).with_top_codes(5))
+        .unwrap();
+
+    assert_eq!(
+        output,
+        concat!(
+            r#"{"errors": 1,"warnings": 4,"advice": 1,"files": 3,"codes": ["#,
+            r#"{"code": "no-unused-vars","count": 2},{"code": "eqeqeq","count": 1},"#,
+            r#"{"code": "no-\"quote\"","count": 1},{"code": "no-debugger","count": 1}]}"#
+        )
+    );
+}
+
+#[test]
+fn report_handlers_render_batches() {
+    let first = diagnostic();
+    let second = duplicate_key();
+    let batch: [&dyn Diagnostic; 2] = [&first, &second];
+    let render = |handler: &dyn ReportHandler, diagnostics: &[&dyn Diagnostic]| {
+        let mut output = String::new();
+        handler.render_reports(&mut output, diagnostics).unwrap();
+        output
+    };
+
+    let graphical = GraphicalReportHandler::new_themed(GraphicalTheme::none());
+    let mut separately = String::new();
+    for diagnostic in batch {
+        graphical.render_report(&mut separately, diagnostic).unwrap();
+    }
+    assert_eq!(render(&graphical, &batch), separately);
+
+    let json = JSONReportHandler::new();
+    let mut first_json = String::new();
+    json.render_report(&mut first_json, &first).unwrap();
+    let mut second_json = String::new();
+    json.render_report(&mut second_json, &second).unwrap();
+    assert_eq!(render(&json, &batch), format!("[{first_json},{second_json}]"));
+    assert_eq!(render(&json, &[]), "[]");
+
+    let handlers: [Box<dyn ReportHandler>; 2] = [Box::new(graphical), Box::new(json)];
+    for handler in &handlers {
+        let mut output = String::new();
+        handler.render_report(&mut output, &first).unwrap();
+        assert!(output.contains("unexpected token"), "{output}");
+    }
+}
+
+#[test]
+fn report_handlers_stream_into_io_writers() {
+    let first = diagnostic();
+    let second = duplicate_key();
+    let batch: [&dyn Diagnostic; 2] = [&first, &second];
+    let handlers: [Box<dyn ReportHandler>; 2] = [
+        Box::new(GraphicalReportHandler::new_themed(GraphicalTheme::none())),
+        Box::new(JSONReportHandler::new()),
+    ];
+    for handler in &handlers {
+        let mut expected = String::new();
+        handler.render_reports(&mut expected, &batch).unwrap();
+        let mut output = Vec::new();
+        handler.render_reports_io(&mut output, &batch).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+        let mut expected = String::new();
+        handler.render_report(&mut expected, &first).unwrap();
+        let mut output = std::io::BufWriter::new(Vec::new());
+        handler.render_report_io(&mut output, &first).unwrap();
+        assert_eq!(output.into_inner().unwrap(), expected.as_bytes());
+    }
+
+    // Write errors come back as they are instead of as `fmt::Error`.
+    let mut full = [0; 16];
+    let error = handlers[1].render_report_io(&mut full.as_mut_slice(), &first).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::WriteZero);
+
+    // Failures that are not writes are about the diagnostic.
+    let past_the_end = MietteDiagnostic::new("oops")
+        .with_label(LabeledSpan::at(100..101, "here"))
+        .with_source_code(Arc::new(NamedSource::new("a.js", "let a;")));
+    let error = handlers[0].render_report_io(&mut Vec::new(), &past_the_end).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn graphical_renderer_batches_match_separate_reports() {
+    let mut text = String::new();
+    for i in 0..2000 {
+        fmt::Write::write_fmt(&mut text, format_args!("const value{i} = {i};\r\n")).unwrap();
+    }
+    let len = u32::try_from(text.len()).unwrap();
+    let file: Arc<dyn SourceCode> = Arc::new(NamedSource::new("big.ts", text));
+    let other: Arc<dyn SourceCode> = Arc::new(NamedSource::new("other.ts", "a\nb\nc\n"));
+    // Offsets jump around the file, so later reports point before earlier ones.
+    let mut offset = 7;
+    let lints: Vec<MietteDiagnostic> = (0..60)
+        .map(|i| {
+            offset = (offset * 7919 + 13) % (len - 3);
+            let lint = MietteDiagnostic::new(format!("lint {i}"))
+                .with_label(LabeledSpan::at(offset..offset + 3, "here"))
+                .with_label(LabeledSpan::at(offset / 2..offset / 2 + 1, "and here"))
+                .with_source_code(Arc::clone(&file));
+            if i % 5 == 0 {
+                lint.with_label(LabeledSpan::at(2..3, "there").with_source_code(Arc::clone(&other)))
+            } else {
+                lint
+            }
+        })
+        .collect();
+    let related = duplicate_key();
+    let cause = LoadConfig { cause: LoadCause::Parse(diagnostic()) };
+    let grouped = type_mismatch();
+    let mut batch: Vec<&dyn Diagnostic> = vec![&related, &cause, &grouped];
+    batch.extend(lints.iter().map(|lint| lint as &dyn Diagnostic));
+
+    let handler = GraphicalReportHandler::new_themed(GraphicalTheme::unicode()).with_width(80);
+    let mut separately = String::new();
+    for &diagnostic in &batch {
+        handler.render_report(&mut separately, diagnostic).unwrap();
+    }
+    let mut together = String::new();
+    handler.render_reports(&mut together, &batch).unwrap();
+    assert_eq!(together, separately);
+
+    let mut through_trait = String::new();
+    ReportHandler::render_reports(&handler, &mut through_trait, &batch).unwrap();
+    assert_eq!(through_trait, separately);
+}
+
+/// A source that keeps its own line table, like an editor's open document.
+struct Indexed {
+    text: String,
+    line_starts: Vec<usize>,
+}
+
+impl SourceCode for Indexed {
+    fn data(&self) -> &[u8] {
+        self.text.as_bytes()
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some("indexed.ts")
+    }
+
+    fn line_starts(&self) -> Option<&[usize]> {
+        Some(&self.line_starts)
+    }
+}
+
+#[test]
+fn renderers_use_precomputed_line_starts_like_scanned_ones() {
+    let mut text = String::new();
+    for (i, line_break) in ["\n", "\r\n", "\r"].into_iter().cycle().take(300).enumerate() {
+        fmt::Write::write_fmt(&mut text, format_args!("let v{i} = {i};{line_break}")).unwrap();
+    }
+    let len = u32::try_from(text.len()).unwrap();
+    let line_starts = SourceIndex::new(&text).line_starts().to_vec();
+    let scanned: Arc<dyn SourceCode> = Arc::new(NamedSource::new("indexed.ts", text.clone()));
+    let indexed: Arc<dyn SourceCode> = Arc::new(Indexed { text, line_starts });
+
+    let lints = |source: &Arc<dyn SourceCode>| -> Vec<MietteDiagnostic> {
+        let mut offset = 3;
+        (0..20)
+            .map(|i| {
+                offset = (offset * 7919 + 13) % (len - 2);
+                MietteDiagnostic::new(format!("lint {i}"))
+                    .with_label(LabeledSpan::at(offset..offset + 2, "here"))
+                    .with_label(LabeledSpan::at(offset / 3..offset / 3 + 1, "and here"))
+                    .with_label(LabeledSpan::at(len..len, "at the end"))
+                    .with_source_code(Arc::clone(source))
+            })
+            .collect()
+    };
+    let render = |handler: &dyn ReportHandler, source: &Arc<dyn SourceCode>| {
+        let lints = lints(source);
+        let batch: Vec<&dyn Diagnostic> =
+            lints.iter().map(|lint| lint as &dyn Diagnostic).collect();
+        let mut output = String::new();
+        handler.render_reports(&mut output, &batch).unwrap();
+        output
+    };
+
+    let graphical = GraphicalReportHandler::new_themed(GraphicalTheme::unicode()).with_width(80);
+    assert_eq!(render(&graphical, &indexed), render(&graphical, &scanned));
+    let json = JSONReportHandler::new();
+    assert_eq!(render(&json, &indexed), render(&json, &scanned));
+}
+
+#[test]
+fn renderers_scan_sources_with_malformed_line_starts() {
+    let text = String::from("let a = 1;\nlet b = 2;\r\nlet c = ;\n");
+    let scanned: Arc<dyn SourceCode> = Arc::new(NamedSource::new("indexed.ts", text.clone()));
+    let render = |handler: &dyn ReportHandler, source: &Arc<dyn SourceCode>| {
+        let lint = MietteDiagnostic::new("expected an expression")
+            .with_labels([LabeledSpan::at(4..5, "declared here"), LabeledSpan::at(31..32, "here")])
+            .with_source_code(Arc::clone(source));
+        let mut output = String::new();
+        handler.render_report(&mut output, &lint).unwrap();
+        output
+    };
+    let graphical = GraphicalReportHandler::new_themed(GraphicalTheme::unicode()).with_width(80);
+    let json = JSONReportHandler::new();
+    let malformed = [vec![], vec![11, 23], vec![0, 23, 11], vec![0, 11, 11, 23], vec![0, 11, 99]];
+    // Sorted, but with starts that are not right after a line break.
+    let wrong = [vec![0, 5], vec![0, 11, 22], vec![0, 4, 11, 23, 33]];
+    for line_starts in malformed.into_iter().chain(wrong) {
+        let indexed: Arc<dyn SourceCode> =
+            Arc::new(Indexed { text: text.clone(), line_starts: line_starts.clone() });
+        assert_eq!(render(&graphical, &indexed), render(&graphical, &scanned), "{line_starts:?}");
+        assert_eq!(render(&json, &indexed), render(&json, &scanned), "{line_starts:?}");
+        assert_eq!(
+            SourceIndex::new(&*indexed).line_column(31, ColumnUnit::Byte),
+            Some(LineColumn::new(2, 8)),
+            "{line_starts:?}"
+        );
+    }
+}
+
+#[test]
+fn source_indexes_ignore_line_starts_inside_lines() {
+    let text = String::from("ab\ncd\nef\n");
+    for line_starts in [vec![0, 5], vec![0, 2, 3, 4, 9]] {
+        let indexed = Indexed { text: text.clone(), line_starts: line_starts.clone() };
+        assert_eq!(
+            SourceIndex::new(&indexed).line_column(4, ColumnUnit::Byte),
+            Some(LineColumn::new(1, 1)),
+            "{line_starts:?}"
+        );
+    }
+}
+
+#[test]
+fn github_actions_renderer_writes_workflow_commands() {
+    let source: Arc<dyn SourceCode> =
+        Arc::new(NamedSource::new("src/a,b:c.js", String::from("let x = 1;\r\nvar 🦀 = 2;\n")));
+    let lint = MietteDiagnostic::new("unexpected `var`\n100% sure")
+        .with_code("lint::no-var")
+        .with_severity(Severity::Warning)
+        .with_help("use `let`")
+        .with_note("see the style guide")
+        .with_labels([
+            LabeledSpan::at(16..20, "this binding"),
+            LabeledSpan::at(12..15, "declared here").with_kind(LabelKind::Primary),
+        ])
+        .with_source_code(Arc::clone(&source));
+    let advice = MietteDiagnostic::new("prefer `const`")
+        .with_severity(Severity::Advice)
+        .with_label(LabeledSpan::at(16..20, "never reassigned"))
+        .with_source_code(source);
+    let unlocated = MietteDiagnostic::new("no location");
+
+    let mut output = String::new();
+    ReportHandler::render_reports(
+        &GitHubActionsReportHandler::new(),
+        &mut output,
+        &[&lint, &advice, &unlocated],
+    )
+    .unwrap();
+
+    let expected = concat!(
+        "::warning file=src/a%2Cb%3Ac.js,line=2,col=1,endLine=2,endColumn=4,title=lint%3A%3Ano-var",
+        "::unexpected `var`%0A100%25 sure%0A%0Ahelp: use `let`%0A%0Anote: see the style guide\n",
+        "::notice file=src/a%2Cb%3Ac.js,line=2,col=5,endLine=2,endColumn=7::prefer `const`\n",
+        "::error::no location\n",
+    );
+    assert_eq!(output, expected);
+
+    let mut single = String::new();
+    GitHubActionsReportHandler::new().render_report(&mut single, &unlocated).unwrap();
+    assert_eq!(single, "::error::no location\n");
+}
+
+fn code_quality_issues(handler: &CodeQualityReportHandler, text: &str) -> serde_json::Value {
+    let offset = u32::try_from(text.find("var").unwrap()).unwrap();
+    let source: Arc<dyn SourceCode> = Arc::new(NamedSource::new("src/main.js", text.to_owned()));
+    let lint = |severity| {
+        MietteDiagnostic::new("unexpected `var`")
+            .with_code("no-var")
+            .with_severity(severity)
+            .with_label(LabeledSpan::at(offset..offset + 3, "here"))
+            .with_source_code(Arc::clone(&source))
+    };
+    let diagnostics = [
+        lint(Severity::Error),
+        lint(Severity::Advice),
+        MietteDiagnostic::new("oops").with_source_code(Arc::clone(&source)),
+        MietteDiagnostic::new("not in a file"),
+    ];
+    let batch: Vec<&dyn Diagnostic> = diagnostics.iter().map(|d| d as &dyn Diagnostic).collect();
+    let mut report = String::new();
+    handler.render_reports(&mut report, &batch).unwrap();
+    serde_json::from_str(&report).unwrap()
+}
+
+#[test]
+fn code_quality_renderer_writes_issues() {
+    let issues = code_quality_issues(&CodeQualityReportHandler::new(), "let a;\nvar 🦀 = 1;\n");
+    assert_eq!(
+        issues[0],
+        serde_json::json!({
+            "type": "issue",
+            "description": "unexpected `var`",
+            "check_name": "no-var",
+            "fingerprint": issues[0]["fingerprint"],
+            "severity": "major",
+            "location": {
+                "path": "src/main.js",
+                "lines": {"begin": 2, "end": 2},
+                "positions": {"begin": {"line": 2, "column": 1}, "end": {"line": 2, "column": 4}}
+            }
+        })
+    );
+    assert_eq!(issues[1]["severity"], "info");
+    assert_eq!(issues[2]["check_name"], "miette");
+    assert_eq!(
+        issues[2]["location"],
+        serde_json::json!({"path": "src/main.js", "lines": {"begin": 1}})
+    );
+    // Diagnostics without a file have nowhere to be shown.
+    assert_eq!(issues.as_array().unwrap().len(), 3);
+
+    let handler = CodeQualityReportHandler::new()
+        .with_severity(Severity::Error, CodeQualitySeverity::Blocker);
+    assert_eq!(code_quality_issues(&handler, "var x;")[0]["severity"], "blocker");
+}
+
+#[test]
+fn code_quality_fingerprints_survive_unrelated_line_moves() {
+    let handler = CodeQualityReportHandler::new();
+    let before = code_quality_issues(&handler, "let a;\nvar x = 1;\n");
+    let after = code_quality_issues(&handler, "// header\n\nlet a;\n  var x = 1;\r\n");
+    let fingerprints = |issues: &serde_json::Value| {
+        issues
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|issue| issue["fingerprint"].clone())
+            .collect::<Vec<_>>()
+    };
+
+    assert_ne!(before[0]["location"], after[0]["location"]);
+    assert_eq!(fingerprints(&before), fingerprints(&after));
+    // The same lint twice on a line still gets two fingerprints.
+    assert_ne!(before[0]["fingerprint"], before[1]["fingerprint"]);
+
+    let edited = code_quality_issues(&handler, "let a;\nvar y = 1;\n");
+    assert_ne!(fingerprints(&before)[0], fingerprints(&edited)[0]);
+}
+
+#[test]
+fn checkstyle_renderer_groups_errors_by_file() {
+    let main: Arc<dyn SourceCode> =
+        Arc::new(NamedSource::new("src/<main>.js", String::from("let a;\r\n\tvar é = 1;\n")));
+    let other: Arc<dyn SourceCode> = Arc::new(NamedSource::new("src/other.js", String::from("x")));
+    let diagnostics = [
+        MietteDiagnostic::new("unexpected \"var\" & <more>\non two lines")
+            .with_code("no-var")
+            .with_label(LabeledSpan::at(14..16, "é"))
+            .with_label(LabeledSpan::at(9..12, "var").with_kind(LabelKind::Primary))
+            .with_source_code(Arc::clone(&main)),
+        MietteDiagnostic::new("in another file")
+            .with_severity(Severity::Advice)
+            .with_label(LabeledSpan::at(0..1, "x"))
+            .with_source_code(other),
+        MietteDiagnostic::new("unused")
+            .with_severity(Severity::Warning)
+            .with_label(LabeledSpan::at(4..5, "a"))
+            .with_source_code(main),
+        MietteDiagnostic::new("no location"),
+    ];
+    let batch: Vec<&dyn Diagnostic> = diagnostics.iter().map(|d| d as &dyn Diagnostic).collect();
+    let mut output = String::new();
+    ReportHandler::render_reports(&CheckstyleReportHandler::new(), &mut output, &batch).unwrap();
+
+    let document = roxmltree::Document::parse(&output).unwrap();
+    let files: Vec<_> = document
+        .root_element()
+        .children()
+        .filter(roxmltree::Node::is_element)
+        .map(|file| {
+            let errors: Vec<Vec<_>> = file
+                .children()
+                .filter(roxmltree::Node::is_element)
+                .map(|error| error.attributes().map(|a| (a.name(), a.value())).collect())
+                .collect();
+            (file.attribute("name").unwrap(), errors)
+        })
+        .collect();
+    assert_eq!(
+        files,
+        [
+            (
+                "src/<main>.js",
+                vec![
+                    vec![
+                        ("line", "2"),
+                        ("column", "2"),
+                        ("severity", "error"),
+                        ("message", "unexpected \"var\" & <more>\non two lines"),
+                        ("source", "no-var"),
+                    ],
+                    vec![
+                        ("line", "1"),
+                        ("column", "5"),
+                        ("severity", "warning"),
+                        ("message", "unused"),
+                    ],
+                ]
+            ),
+            (
+                "src/other.js",
+                vec![vec![
+                    ("line", "1"),
+                    ("column", "1"),
+                    ("severity", "info"),
+                    ("message", "in another file")
+                ]]
+            ),
+            ("", vec![vec![("severity", "error"), ("message", "no location")]]),
+        ]
+    );
+
+    // Positions agree with the JSON renderer's.
+    let mut json = String::new();
+    JSONReportHandler::new().render_report(&mut json, &diagnostics[0]).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let primary = &json["labels"][1]["span"];
+    assert_eq!(
+        (&primary["line"], &primary["column"]),
+        (&serde_json::json!(2), &serde_json::json!(2))
+    );
+}
+
+#[test]
+fn junit_renderer_embeds_plain_reports_as_failures() {
+    let main: Arc<dyn SourceCode> =
+        Arc::new(NamedSource::new("src/main.js", String::from("let a = b[c[0]]>1;\n")));
+    let diagnostics = [
+        MietteDiagnostic::new("suspicious `]]>` & <comparison>")
+            .with_code("no-cdata")
+            .with_label(LabeledSpan::at(8..16, "this ends in ]]>"))
+            .with_source_code(Arc::clone(&main)),
+        MietteDiagnostic::new("unused")
+            .with_severity(Severity::Warning)
+            .with_label(LabeledSpan::at(4..5, "a"))
+            .with_source_code(Arc::clone(&main)),
+        MietteDiagnostic::new("prefer `const`")
+            .with_severity(Severity::Advice)
+            .with_label(LabeledSpan::at(0..3, "let"))
+            .with_source_code(Arc::new(NamedSource::new("src/lib.js", String::from("let x;")))),
+    ];
+    let batch: Vec<&dyn Diagnostic> = diagnostics.iter().map(|d| d as &dyn Diagnostic).collect();
+    let handler = JUnitReportHandler::new().with_width(80).with_files([
+        "src/main.js",
+        "src/lib.js",
+        "src/util.js",
+    ]);
+    let mut output = String::new();
+    ReportHandler::render_reports(&handler, &mut output, &batch).unwrap();
+
+    let document = roxmltree::Document::parse(&output).unwrap();
+    let root = document.root_element();
+    assert_eq!((root.attribute("tests"), root.attribute("failures")), (Some("4"), Some("2")));
+    let suites: Vec<_> = root
+        .children()
+        .filter(roxmltree::Node::is_element)
+        .map(|suite| {
+            let cases: Vec<_> = suite
+                .children()
+                .filter(roxmltree::Node::is_element)
+                .map(|case| {
+                    let failure = case.first_element_child();
+                    (
+                        case.attribute("name").unwrap(),
+                        failure.and_then(|failure| failure.attribute("type")),
+                        failure.and_then(|failure| failure.text()).unwrap_or_default().to_owned(),
+                    )
+                })
+                .collect();
+            (suite.attribute("name").unwrap(), suite.attribute("failures").unwrap(), cases)
+        })
+        .collect();
+
+    let plain = |diagnostic: &MietteDiagnostic| {
+        let mut report = String::new();
+        GraphicalReportHandler::new_themed(GraphicalTheme::none())
+            .with_links(false)
+            .with_width(80)
+            .render_report(&mut report, diagnostic)
+            .unwrap();
+        report
+    };
+    assert!(plain(&diagnostics[0]).contains("]]>"));
+    assert_eq!(
+        suites,
+        [
+            (
+                "src/main.js",
+                "2",
+                vec![
+                    ("no-cdata", Some("error"), plain(&diagnostics[0])),
+                    ("unused", Some("warning"), plain(&diagnostics[1])),
+                ]
+            ),
+            ("src/lib.js", "0", vec![("src/lib.js", None, String::new())]),
+            ("src/util.js", "0", vec![("src/util.js", None, String::new())]),
+        ]
+    );
+}
+
+#[test]
+fn junit_renderer_falls_back_to_the_message_when_a_report_fails() {
+    let diagnostics = [
+        MietteDiagnostic::new("unexpected end of file")
+            .with_code("parser::eof")
+            .with_help("close the <block>")
+            .with_note("opened on line 1")
+            .with_label(LabeledSpan::at(40..41, "past the end"))
+            .with_source_code(Arc::new(NamedSource::new("src/main.js", String::from("{\n")))),
+        MietteDiagnostic::new("unused").with_severity(Severity::Warning),
+    ];
+    let batch: Vec<&dyn Diagnostic> = diagnostics.iter().map(|d| d as &dyn Diagnostic).collect();
+    let mut output = String::new();
+    JUnitReportHandler::new().render_reports(&mut output, &batch).unwrap();
+
+    let document = roxmltree::Document::parse(&output).unwrap();
+    let failures: Vec<_> = document
+        .descendants()
+        .filter(|node| node.has_tag_name("failure"))
+        .map(|failure| failure.text().unwrap_or_default().to_owned())
+        .collect();
+    assert_eq!(failures.len(), 2);
+    assert_eq!(
+        failures[0],
+        "unexpected end of file\n\nhelp: close the <block>\n\nnote: opened on line 1"
+    );
+    assert!(failures[1].contains("unused"), "{}", failures[1]);
+}
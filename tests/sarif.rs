@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use miette::{
+    Diagnostic, LabelKind, LabeledSpan, MietteDiagnostic, NamedSource, ReportHandler,
+    SarifReportHandler, Severity, SourceCode, SourceFragment, SourceOrigin,
+};
+use serde_json::{Value, json};
+
+fn source(name: &str, text: &str) -> Arc<dyn SourceCode> {
+    Arc::new(NamedSource::new(name, text.to_owned()))
+}
+
+/// A lint with a primary and a secondary label, some with help and a URL.
+fn diagnostics() -> Vec<MietteDiagnostic> {
+    let main = source("src\\main file.js", "let café = 1;\r\nvar 🦀 = 2;\n");
+    let other = source("src/other.js", "export const a = 1;\n");
+    vec![
+        MietteDiagnostic::new("unexpected `var`")
+            .with_code("no-var")
+            .with_help("use `let` or `const` instead")
+            .with_url("https://example.com/rules/no-var")
+            .with_labels([
+                LabeledSpan::at(20..24, "this binding"),
+                LabeledSpan::at(16..19, "declared here").with_kind(LabelKind::Primary),
+            ])
+            .with_source_code(Arc::clone(&main)),
+        MietteDiagnostic::new("unused variable")
+            .with_code("no-unused-vars")
+            .with_severity(Severity::Warning)
+            .with_labels([
+                LabeledSpan::at(4..9, "never read"),
+                LabeledSpan::at(13..13, "exported from here").with_source_code(Arc::clone(&other)),
+                LabeledSpan::at(0..1, "left out").with_source_code(Arc::new("unnamed")),
+            ])
+            .with_source_code(Arc::clone(&main)),
+        MietteDiagnostic::new("prefer `const`")
+            .with_code("no-var")
+            .with_severity(Severity::Advice)
+            .with_label(LabeledSpan::at(0..3, "never reassigned"))
+            .with_source_code(Arc::new(SourceFragment::new(
+                NamedSource::new("App.vue", String::from("let x;")),
+                SourceOrigin::new(30, 2, 8),
+            ))),
+        MietteDiagnostic::new("no location"),
+    ]
+}
+
+fn render(diagnostics: &[MietteDiagnostic]) -> Value {
+    let batch: Vec<&dyn Diagnostic> = diagnostics.iter().map(|d| d as &dyn Diagnostic).collect();
+    let mut log = String::new();
+    SarifReportHandler::new("lint \"tool\"")
+        .with_tool_version("1.2.0")
+        .render_reports(&mut log, &batch)
+        .unwrap();
+    serde_json::from_str(&log).unwrap_or_else(|error| panic!("{error}: {log}"))
+}
+
+#[test]
+fn sarif_log_validates_against_the_schema() {
+    let schema: Value =
+        serde_json::from_str(include_str!("fixtures/sarif-2.1.0.schema.json")).unwrap();
+    let validator = jsonschema::options().should_validate_formats(true).build(&schema).unwrap();
+
+    for log in [render(&diagnostics()), render(&[])] {
+        let errors: Vec<_> = validator.iter_errors(&log).map(|error| error.to_string()).collect();
+        assert!(errors.is_empty(), "{errors:#?}\n{log:#}");
+    }
+}
+
+#[test]
+fn sarif_maps_diagnostics_to_rules_and_results() {
+    let log = render(&diagnostics());
+    let run = &log["runs"][0];
+
+    assert_eq!(run["tool"]["driver"]["name"], "lint \"tool\"");
+    assert_eq!(run["tool"]["driver"]["version"], "1.2.0");
+    assert_eq!(
+        run["tool"]["driver"]["rules"],
+        json!([
+            {
+                "id": "no-var",
+                "help": {"text": "use `let` or `const` instead"},
+                "helpUri": "https://example.com/rules/no-var"
+            },
+            {"id": "no-unused-vars"}
+        ])
+    );
+
+    let results = run["results"].as_array().unwrap();
+    let levels: Vec<_> = results.iter().map(|result| &result["level"]).collect();
+    assert_eq!(levels, ["error", "warning", "note", "error"]);
+    let rule_indices: Vec<_> = results.iter().map(|result| &result["ruleIndex"]).collect();
+    assert_eq!(rule_indices, [&json!(0), &json!(1), &json!(0), &Value::Null]);
+    assert_eq!(results[3], json!({"level": "error", "message": {"text": "no location"}}));
+}
+
+#[test]
+fn sarif_regions_count_utf16_columns() {
+    let log = render(&diagnostics());
+    let results = &log["runs"][0]["results"];
+
+    // The primary label is the location, even though it comes second.
+    assert_eq!(
+        results[0]["locations"],
+        json!([{
+            "physicalLocation": {
+                "artifactLocation": {"uri": "src/main%20file.js"},
+                "region": {
+                    "startLine": 2,
+                    "startColumn": 1,
+                    "endLine": 2,
+                    "endColumn": 4,
+                    "byteOffset": 16,
+                    "byteLength": 3,
+                    "snippet": {"text": "var"}
+                }
+            },
+            "message": {"text": "declared here"}
+        }])
+    );
+    // 🦀 is two UTF-16 code units.
+    let region = &results[0]["relatedLocations"][0]["physicalLocation"]["region"];
+    assert_eq!(
+        (&region["startColumn"], &region["endColumn"], &region["snippet"]["text"]),
+        (&json!(5), &json!(7), &json!("🦀"))
+    );
+
+    // Labels into other files keep their own file, and labels into unnamed
+    // sources are left out.
+    let related = results[1]["relatedLocations"].as_array().unwrap();
+    assert_eq!(related.len(), 1);
+    assert_eq!(related[0]["physicalLocation"]["artifactLocation"]["uri"], "src/other.js");
+    let region = &results[1]["locations"][0]["physicalLocation"]["region"];
+    assert_eq!((&region["startColumn"], &region["endColumn"]), (&json!(5), &json!(9)));
+
+    // Fragments are located in the file they were taken from.
+    let region = &results[2]["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(
+        (&region["startLine"], &region["startColumn"], &region["byteOffset"]),
+        (&json!(3), &json!(9), &json!(30))
+    );
+}
+
+#[test]
+fn sarif_single_reports_are_logs_of_their_own() {
+    let diagnostic = MietteDiagnostic::new("unexpected token");
+    let handler = SarifReportHandler::new("lint");
+    let mut through_trait = String::new();
+    ReportHandler::render_report(&handler, &mut through_trait, &diagnostic).unwrap();
+    let mut batch = String::new();
+    handler.render_reports(&mut batch, &[&diagnostic]).unwrap();
+
+    assert_eq!(through_trait, batch);
+    let log: Value = serde_json::from_str(&through_trait).unwrap();
+    assert_eq!(log["version"], "2.1.0");
+    assert_eq!(log["runs"][0]["results"][0]["message"]["text"], "unexpected token");
+}
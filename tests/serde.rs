@@ -0,0 +1,104 @@
+#![cfg(feature = "serde")]
+
+use std::sync::Arc;
+
+use miette::{
+    Diagnostic, DiagnosticSnapshot, JSONReportHandler, LabelKind, LabeledSpan, MietteDiagnostic,
+    NamedSource, Severity, SourceSpan,
+};
+use serde_json::json;
+
+#[test]
+fn protocol_types_round_trip() {
+    assert_eq!(serde_json::to_value(Severity::Warning).unwrap(), json!("warning"));
+    assert_eq!(serde_json::from_value::<Severity>(json!("advice")).unwrap(), Severity::Advice);
+
+    let span = SourceSpan::from(4..6);
+    assert_eq!(serde_json::to_value(span).unwrap(), json!({"offset": 4, "length": 2}));
+    assert_eq!(
+        serde_json::from_value::<SourceSpan>(json!({"offset": 4, "length": 2})).unwrap(),
+        span
+    );
+
+    let label = LabeledSpan::new_with_kind(Some("here".into()), 4..6, LabelKind::Primary);
+    let value = json!({"label": "here", "span": {"offset": 4, "length": 2}, "kind": "primary"});
+    assert_eq!(serde_json::to_value(&label).unwrap(), value);
+    assert_eq!(serde_json::from_value::<LabeledSpan>(value).unwrap(), label);
+
+    let value = json!({"span": {"offset": 0, "length": 1}});
+    assert_eq!(serde_json::from_value::<LabeledSpan>(value).unwrap(), LabeledSpan::new(None, 0, 1));
+}
+
+#[test]
+fn label_sources_are_not_serialized() {
+    let source = Arc::new(NamedSource::new("types.d.ts", "type Id = string;"));
+    let label = LabeledSpan::at(5..7, "declared here").with_source_code(source);
+    let value = serde_json::to_value(&label).unwrap();
+    assert_eq!(
+        value,
+        json!({"label": "declared here", "span": {"offset": 5, "length": 2}, "kind": "secondary"})
+    );
+    assert!(serde_json::from_value::<LabeledSpan>(value).unwrap().source_code().is_none());
+}
+
+#[test]
+fn snapshots_drop_label_sources() {
+    let source = Arc::new(NamedSource::new("types.d.ts", "type Id = string;"));
+    let label = LabeledSpan::at(5..7, "declared here").with_source_code(source);
+    let diagnostic = diagnostic().with_label(label);
+    let snapshot = DiagnosticSnapshot::new(&diagnostic);
+    assert_eq!(snapshot.labels.len(), 2);
+    assert!(snapshot.labels.iter().all(|label| label.source_code().is_none()));
+    assert_eq!(snapshot.labels[1].label(), Some("declared here"));
+    assert_eq!(snapshot.labels[1].inner(), &(5..7).into());
+}
+
+fn diagnostic() -> MietteDiagnostic {
+    MietteDiagnostic::new("unexpected token")
+        .with_code("parser::unexpected")
+        .with_severity(Severity::Warning)
+        .with_help("remove it")
+        .with_url("https://oxc.rs")
+        .with_label(LabeledSpan::new_primary_with_span(Some("here".into()), 4..5))
+        .with_source_code(Arc::new(NamedSource::new("test.js", "let ? = 1;")))
+}
+
+#[test]
+fn snapshots_serialize_like_the_json_renderer() {
+    let diagnostic = diagnostic();
+    let snapshot = DiagnosticSnapshot::new(&diagnostic);
+    let value = serde_json::to_value(&snapshot).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "message": "unexpected token",
+            "code": "parser::unexpected",
+            "severity": "warning",
+            "help": "remove it",
+            "url": "https://oxc.rs",
+            "filename": "test.js",
+            "labels": [{"label": "here", "span": {"offset": 4, "length": 1}, "kind": "primary"}],
+        })
+    );
+    assert_eq!(serde_json::from_value::<DiagnosticSnapshot>(value).unwrap(), snapshot);
+
+    // The renderer's output carries more, which a snapshot ignores.
+    let mut rendered = String::new();
+    JSONReportHandler::new().render_report(&mut rendered, &diagnostic).unwrap();
+    let parsed: DiagnosticSnapshot = serde_json::from_str(&rendered).unwrap();
+    assert_eq!(parsed, snapshot);
+}
+
+#[test]
+fn snapshots_are_diagnostics() {
+    let snapshot: DiagnosticSnapshot =
+        serde_json::from_value(json!({"message": "oops", "labels": []})).unwrap();
+    assert_eq!(snapshot.to_string(), "oops");
+    assert_eq!(snapshot.severity(), Some(Severity::Error));
+    assert_eq!(snapshot.code(), None);
+    assert!(snapshot.source_code().is_none());
+
+    let boxed: Box<dyn Diagnostic + Send + Sync> = Box::new(DiagnosticSnapshot::new(&diagnostic()));
+    assert_eq!(boxed.code().as_deref(), Some("parser::unexpected"));
+    assert_eq!(boxed.labels().len(), 1);
+}
@@ -1,6 +1,10 @@
-use std::sync::Arc;
+use std::{fmt, fs, sync::Arc};
 
-use miette::{NamedSource, SourceCode};
+use miette::{
+    ColumnUnit, Diagnostic, FileSource, GraphicalReportHandler, GraphicalTheme, JSONReportHandler,
+    LabeledSpan, LineColumn, MappedSource, MietteDiagnostic, NamedSource, SourceCode,
+    SourceFragment, SourceIndex, SourceMapError, SourceOrigin, Suggestion,
+};
 
 #[test]
 fn basic_sources_have_no_name() {
@@ -34,3 +38,344 @@ fn arc_named_source_returns_name() {
     assert_eq!(SourceCode::name(&named), Some("main.rs"));
     assert_eq!(named.name(), Some("main.rs"));
 }
+
+fn pos(line: usize, column: usize) -> LineColumn {
+    LineColumn::new(line, column)
+}
+
+#[test]
+fn source_index_counts_columns_in_every_unit() {
+    // "é" is 2 bytes, "🦀" 4 bytes / 2 UTF-16 units, "e\u{301}" one grapheme of two chars.
+    let source = "aé🦀e\u{301}x\r\nnext";
+    let index = SourceIndex::new(&source);
+    let x = 10;
+    assert_eq!(&source[x as usize..=x as usize], "x");
+    assert_eq!(index.line_column(x, ColumnUnit::Byte), Some(pos(0, 10)));
+    assert_eq!(index.line_column(x, ColumnUnit::Char), Some(pos(0, 5)));
+    assert_eq!(index.line_column(x, ColumnUnit::Utf16), Some(pos(0, 6)));
+    assert_eq!(index.line_column(x, ColumnUnit::Grapheme), Some(pos(0, 4)));
+
+    for unit in [ColumnUnit::Byte, ColumnUnit::Char, ColumnUnit::Utf16, ColumnUnit::Grapheme] {
+        let position = index.line_column(x, unit).unwrap();
+        assert_eq!(index.offset(position, unit), Some(x), "{unit:?}");
+        assert_eq!(index.line_column(x + 3, unit), Some(pos(1, 0)), "{unit:?}");
+    }
+}
+
+#[test]
+fn source_index_handles_line_breaks_and_bounds() {
+    let source = "a\r\nb\rc\n";
+    let index = SourceIndex::new(&source);
+    assert_eq!(index.line_count(), 4);
+    assert_eq!(index.line_start(1), Some(3));
+    assert_eq!(index.line_start(2), Some(5));
+    assert_eq!(index.line_start(3), Some(7));
+    assert_eq!(index.line_start(4), None);
+
+    // Offsets inside a line break sit at the end of their line.
+    assert_eq!(index.line_column(1, ColumnUnit::Byte), Some(pos(0, 1)));
+    assert_eq!(index.line_column(2, ColumnUnit::Byte), Some(pos(0, 1)));
+    assert_eq!(index.line_column(7, ColumnUnit::Byte), Some(pos(3, 0)));
+    assert_eq!(index.line_column(8, ColumnUnit::Byte), None);
+
+    assert_eq!(index.offset(pos(0, 1), ColumnUnit::Char), Some(1));
+    assert_eq!(index.offset(pos(0, 2), ColumnUnit::Char), None);
+    assert_eq!(index.offset(pos(3, 0), ColumnUnit::Byte), Some(7));
+    assert_eq!(index.offset(pos(4, 0), ColumnUnit::Byte), None);
+}
+
+#[test]
+fn source_index_rejects_positions_inside_characters() {
+    let source = "🦀!";
+    let index = SourceIndex::new(&source);
+    assert_eq!(index.line_column(2, ColumnUnit::Char), Some(pos(0, 0)));
+    assert_eq!(index.offset(pos(0, 1), ColumnUnit::Utf16), None);
+    assert_eq!(index.offset(pos(0, 2), ColumnUnit::Utf16), Some(4));
+    assert_eq!(index.offset(pos(0, 1), ColumnUnit::Char), Some(4));
+}
+
+#[test]
+fn source_index_counts_invalid_utf8_bytewise() {
+    struct Bytes(&'static [u8]);
+    impl SourceCode for Bytes {
+        fn data(&self) -> &[u8] {
+            self.0
+        }
+    }
+
+    let source = NamedSource::new("bin", String::new());
+    assert_eq!(SourceIndex::new(&source).line_count(), 1);
+
+    let bytes = Bytes(b"\xff\xfe\xc3\xa9z");
+    let index = SourceIndex::new(&bytes);
+    assert_eq!(index.line_column(4, ColumnUnit::Char), Some(pos(0, 3)));
+    assert_eq!(index.offset(pos(0, 3), ColumnUnit::Utf16), Some(4));
+}
+
+#[test]
+fn labels_can_be_built_from_positions() {
+    let source = "let a = 1;\nlet 🦀 = 2;";
+    let index = SourceIndex::new(&source);
+    let label = LabeledSpan::from_line_col(
+        Some("crab".into()),
+        &index,
+        pos(1, 4),
+        pos(1, 6),
+        ColumnUnit::Utf16,
+    );
+    assert_eq!(label, Some(LabeledSpan::at(15..19, "crab")));
+    assert_eq!(
+        LabeledSpan::from_line_col(None, &index, pos(1, 6), pos(1, 4), ColumnUnit::Utf16),
+        None
+    );
+    assert_eq!(
+        LabeledSpan::from_line_col(None, &index, pos(1, 4), pos(1, 5), ColumnUnit::Utf16),
+        None
+    );
+    assert_eq!(
+        LabeledSpan::from_line_col(None, &index, pos(0, 0), pos(9, 0), ColumnUnit::Byte),
+        None
+    );
+}
+
+#[test]
+fn file_source_reads_lazily() {
+    let path = std::env::temp_dir().join(format!("miette-file-source-{}.js", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let source = FileSource::new(&path);
+    assert_eq!(source.path(), path);
+    assert_eq!(source.name(), Some(path.to_string_lossy().as_ref()));
+
+    // Nothing is read until the contents are asked for.
+    fs::write(&path, "let a = 1;\n").unwrap();
+    assert!(source.is_available());
+    assert_eq!(source.data(), b"let a = 1;\n");
+    assert!(source.error().is_none());
+
+    // Once read, the contents stay put.
+    fs::remove_file(&path).unwrap();
+    assert_eq!(source.data(), b"let a = 1;\n");
+}
+
+#[test]
+fn unreadable_files_render_without_snippets() {
+    let source = Arc::new(FileSource::new("does/not/exist.js"));
+    assert!(source.error().is_some());
+    let diagnostic = MietteDiagnostic::new("unused variable")
+        .with_help("remove it")
+        .with_label(LabeledSpan::at(4..5, "here"))
+        .with_source_code(source);
+
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .render_report(&mut output, &diagnostic)
+        .unwrap();
+    assert_eq!(output, "\n  x unused variable\n  help: remove it\n");
+
+    let mut output = String::new();
+    JSONReportHandler::new().render_report(&mut output, &diagnostic).unwrap();
+    assert!(
+        output.contains(concat!(
+            r#""labels": [{"label": "here","kind": "secondary","filename": "does/not/exist.js","#,
+            r#""span": {"offset": 4,"length": 1,"line": null,"column": null}}]"#
+        )),
+        "{output}"
+    );
+}
+
+/// Implemented by hand, as [`MietteDiagnostic`] has no suggestions.
+#[derive(Debug)]
+struct Compiled {
+    source: MappedSource<NamedSource<&'static str>>,
+    labels: Vec<LabeledSpan>,
+    suggestions: Vec<Suggestion>,
+}
+
+impl fmt::Display for Compiled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unused variable")
+    }
+}
+
+impl std::error::Error for Compiled {}
+
+impl Diagnostic for Compiled {
+    fn labels(&self) -> &[LabeledSpan] {
+        &self.labels
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&self.source)
+    }
+
+    fn suggestions(&self) -> &[Suggestion] {
+        &self.suggestions
+    }
+}
+
+const GENERATED: &str = "const crab = '🦀';\nlet unused = 1;\n";
+const SOURCE_MAP: &str = r#"{
+    "version": 3,
+    "file": "a.js",
+    "sourceRoot": "src",
+    "sources": ["a.ts"],
+    "sourcesContent": ["const crab: string = '🦀';\nlet unused: number = 1;\n"],
+    "names": [],
+    "mappings": "AAAA,MAAM,O;AACN,IAAI,MAAc"
+}"#;
+
+fn compiled(labels: Vec<LabeledSpan>) -> Compiled {
+    let source = NamedSource::new("a.js", GENERATED);
+    Compiled { source: MappedSource::new(source, SOURCE_MAP).unwrap(), labels, suggestions: vec![] }
+}
+
+#[test]
+fn mapped_sources_remap_labels_to_the_original() {
+    let diagnostic = compiled(vec![LabeledSpan::at(25..31, "never read")]);
+    let label = diagnostic.source.remap_label(&diagnostic.labels[0]).unwrap();
+    assert_eq!(label.source_code().and_then(SourceCode::name), Some("src/a.ts"));
+    assert_eq!(label.to_range(), 33..39);
+
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .render_report(&mut output, &diagnostic)
+        .unwrap();
+    let expected = r"
+  x unused variable
+   ,-[src/a.ts:2:5]
+ 1 | const crab: string = '🦀';
+ 2 | let unused: number = 1;
+   :     ^^^|^^
+   :        `-- never read
+   `----
+";
+    assert_eq!(output, expected);
+
+    let mut output = String::new();
+    JSONReportHandler::new().render_report(&mut output, &diagnostic).unwrap();
+    assert!(
+        output.contains(concat!(
+            r#""labels": [{"label": "never read","kind": "secondary","filename": "src/a.ts","#,
+            r#""span": {"offset": 33,"length": 6,"line": 2,"column": 5}}]"#
+        )),
+        "{output}"
+    );
+}
+
+#[test]
+fn unmapped_labels_stay_on_generated_code() {
+    let diagnostic =
+        compiled(vec![LabeledSpan::at(13..19, "crab"), LabeledSpan::underline(13..19)]);
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .render_report(&mut output, &diagnostic)
+        .unwrap();
+    let expected = r"
+  x unused variable
+   ,-[a.js:1:14]
+ 1 | const crab = '🦀';
+   :              ^^|^|
+   :                | `-- no source mapping, shown in generated code
+   :                `-- crab (no source mapping, shown in generated code)
+ 2 | let unused = 1;
+   `----
+";
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn suggestions_stay_on_generated_code() {
+    let diagnostic = Compiled {
+        suggestions: vec![Suggestion::new(21..24, "const")],
+        ..compiled(vec![LabeledSpan::at(25..31, "never read")])
+    };
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .render_report(&mut output, &diagnostic)
+        .unwrap();
+    assert!(output.contains(",-[src/a.ts:2:5]"), "{output}");
+    assert!(
+        output.contains(",-[a.js:2:1]\n 2 | - let unused = 1;\n 2 | + const unused = 1;"),
+        "{output}"
+    );
+
+    let mut output = String::new();
+    JSONReportHandler::new().render_report(&mut output, &diagnostic).unwrap();
+    assert!(
+        output.contains(concat!(
+            r#""suggestions": [{"replacement": "const","applicability": "unspecified","#,
+            r#""span": {"offset": 21,"length": 3,"line": 2,"column": 1}}]"#
+        )),
+        "{output}"
+    );
+}
+
+#[test]
+fn invalid_source_maps_are_rejected() {
+    let error = |map| MappedSource::new("", map).unwrap_err();
+    assert_eq!(error("{"), SourceMapError::Json { offset: 1, message: "expected a string key" });
+    assert_eq!(error(r#"{"version": 2}"#), SourceMapError::UnsupportedVersion);
+    assert_eq!(
+        error(r#"{"version": 3, "sources": [], "mappings": "AAAA"}"#),
+        SourceMapError::InvalidMappings { offset: 0 }
+    );
+    assert_eq!(
+        error(r#"{"version": 3, "sources": ["a.ts"], "mappings": 1}"#),
+        SourceMapError::InvalidField("mappings")
+    );
+    assert_eq!(
+        error(r#"{"version": 3, "sources": "a.ts", "mappings": ""}"#).to_string(),
+        "source map has an invalid `sources` field"
+    );
+}
+
+#[test]
+fn fragments_report_positions_in_the_enclosing_file() {
+    let file = "<template>\n  <div/>\n</template>\n<script>let a = 1;\nlet b = a +;\n</script>\n";
+    let start = file.find("let a").unwrap();
+    let fragment = &file[start..file.find("</script>").unwrap()];
+    let origin = SourceOrigin::new(u32::try_from(start).unwrap(), 3, 8);
+    let diagnostic = MietteDiagnostic::new("unexpected token")
+        .with_labels([
+            LabeledSpan::at(4..5, "declared here"),
+            LabeledSpan::new_primary_with_span(Some("expected an expression".into()), 21..22),
+        ])
+        .with_source_code(Arc::new(NamedSource::new(
+            "App.vue",
+            SourceFragment::new(fragment, origin),
+        )));
+
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::none())
+        .render_report(&mut output, &diagnostic)
+        .unwrap();
+    let expected = r"
+  x unexpected token
+   ,-[App.vue:5:11]
+ 4 | let a = 1;
+   :     |
+   :     `-- declared here
+ 5 | let b = a +;
+   :           ^
+   :           `-- expected an expression
+   `----
+";
+    assert_eq!(output, expected);
+
+    let mut output = String::new();
+    JSONReportHandler::new().render_report(&mut output, &diagnostic).unwrap();
+    assert!(
+        output.contains(concat!(
+            r#"{"label": "declared here","kind": "secondary","filename": "App.vue","#,
+            r#""span": {"offset": 44,"length": 1,"line": 4,"column": 13}}"#,
+        )),
+        "{output}"
+    );
+    assert!(
+        output.contains(concat!(
+            r#"{"label": "expected an expression","kind": "primary","filename": "App.vue","#,
+            r#""span": {"offset": 61,"length": 1,"line": 5,"column": 11}}"#,
+        )),
+        "{output}"
+    );
+}